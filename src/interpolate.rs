@@ -0,0 +1,65 @@
+/// A value that can be linearly interpolated between a previous tick's state
+/// and the current tick's state, using the `phase` of a
+/// [`Reading::Frame`](enum.Reading.html#variant.Frame).
+pub trait Lerp: Sized {
+    /// Return the value `phase` of the way from `prev` to `cur`. A `phase`
+    /// of 0 gives `prev`, and a `phase` of 1 gives `cur`.
+    fn lerp(prev: &Self, cur: &Self, phase: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(prev: &f32, cur: &f32, phase: f32) -> f32 {
+        prev + (cur - prev) * phase
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(prev: &f64, cur: &f64, phase: f32) -> f64 {
+        prev + (cur - prev) * phase as f64
+    }
+}
+
+/// Interpolate a whole slice of values at once. Element `i` of `out` becomes
+/// `phase` of the way from `prev[i]` to `cur[i]`. Handy when you have
+/// thousands of entities sharing the same frame phase.
+///
+/// PANICS if the three slices are not all the same length!
+pub fn interpolate_slice<T: Lerp>(prev: &[T], cur: &[T], phase: f32, out: &mut [T]) {
+    assert_eq!(prev.len(), cur.len(), "prev and cur must be the same length.");
+    assert_eq!(prev.len(), out.len(), "out must be the same length as prev and cur.");
+    for ((prev, cur), out) in prev.iter().zip(cur.iter()).zip(out.iter_mut()) {
+        *out = T::lerp(prev, cur, phase);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn elementwise() {
+        let prev = [0.0f32, 10.0, -4.0, 3.0];
+        let cur = [1.0f32, 20.0, 4.0, 3.0];
+        let mut out = [0.0f32; 4];
+        interpolate_slice(&prev, &cur, 0.25, &mut out);
+        assert_eq!(out, [0.25, 12.5, -2.0, 3.0]);
+        interpolate_slice(&prev, &cur, 0.0, &mut out);
+        assert_eq!(out, prev);
+        interpolate_slice(&prev, &cur, 1.0, &mut out);
+        assert_eq!(out, cur);
+        let mut out = [0.0f64; 2];
+        interpolate_slice(&[2.0f64, 4.0], &[4.0, 2.0], 0.5, &mut out);
+        assert_eq!(out, [3.0, 3.0]);
+    }
+    #[test]
+    #[should_panic]
+    fn length_mismatch() {
+        let mut out = [0.0f32; 2];
+        interpolate_slice(&[0.0, 1.0], &[0.0, 1.0, 2.0], 0.5, &mut out);
+    }
+    #[test]
+    #[should_panic]
+    fn output_length_mismatch() {
+        let mut out = [0.0f32; 3];
+        interpolate_slice(&[0.0, 1.0], &[0.0, 1.0], 0.5, &mut out);
+    }
+}
@@ -5,7 +5,7 @@
 //!
 //! - **Repeatability**: the same inputs will have the same outputs, period.
 //! - **Framerate independence**: no issues like Quake had where your exact
-//! jump height depends on how fast your computer is.
+//!   jump height depends on how fast your computer is.
 //! - **Satisfaction**: knowing that you made the morally correct choice. :)
 //!
 //! Bonus: If you know your refresh rate, `ftvf` can help you render frames at
@@ -50,15 +50,33 @@
 //!   world.handle_input();
 //!   for reading in metronome.sample(Mode::UnlimitedFrames) {
 //!     match reading {
-//!       Reading::Tick => world.perform_tick(),
-//!       Reading::Frame{phase} => world.render(phase),
+//!       Reading::Tick{..} => world.perform_tick(),
+//!       Reading::Frame{phase, ..} => world.render(phase),
 //!       Reading::TimeWentBackwards
 //!         => eprintln!("Warning: time flowed backwards!"),
 //!       Reading::TicksLost
 //!         => eprintln!("Warning: we're too slow, lost some ticks!"),
+//!       // Only produced in strict mode.
+//!       Reading::FramerateMismatch
+//!         => eprintln!("Warning: framerate and tickrate are way off!"),
+//!       // Only produced if you call `set_clock_stall_threshold`.
+//!       Reading::ClockStalled
+//!         => eprintln!("Warning: the clock is stuck!"),
+//!       // Only produced if you call `set_announce_first_tick`.
+//!       Reading::FirstTick => (),
+//!       // Only produced if you call `set_slow_tick_divisor`.
+//!       Reading::SlowTick => (),
+//!       // Only produced after you call `set_tickrate`.
+//!       Reading::TickrateChanged{..} => (),
+//!       // Only produced if your `TemporalSample` runs out of range.
+//!       Reading::EndOfTime => panic!("We ran out of time!"),
+//!       // Only produced if you call `set_never_empty`.
+//!       Reading::NoOp => (),
 //!       // Mode::UnlimitedFrames never returns Idle, but other modes can, and
 //!       // this is one way to handle it.
 //!       Reading::Idle{duration} => std::thread::sleep(duration),
+//!       // Only produced if you use `coalesce_ticks()`.
+//!       Reading::Ticks{count} => for _ in 0 .. count { world.perform_tick() },
 //!     }
 //!   }
 //! }
@@ -84,6 +102,238 @@
 //!
 //! # Changes
 //!
+//! ## Since 0.6.0
+//!
+//! - `Metronome::sample` now returns a nameable `MetronomeIterator`, which can
+//!   tell you up front whether the batch will produce a frame via
+//!   `will_produce_frame()`.
+//! - `Rate::per_second_u64` added, for rates whose numerator or denominator do
+//!   not fit in the one-billion limit of `per_second`. The only real limit is
+//!   that a rate may not be faster than one tick per nanosecond.
+//! - `interpolate_slice` and the `Lerp` trait added, for interpolating many
+//!   values by the same frame phase in one go.
+//! - `Reading::Frame` now has an `on_tick_boundary` field, which is `true` when
+//!   the frame lands exactly on the current tick.
+//! - `Metronome::tick_number` and `Metronome::elapsed_ticks_since` added. Both
+//!   count lost ticks, so they stay consistent with the wall clock.
+//! - Fixed: `max_ticks_behind` was never actually enforced, so `TicksLost`
+//!   could only happen with a `max_ticks_behind` of zero.
+//! - `Mode::RenderOnly` added, for thin clients that render at a target
+//!   framerate but never tick. Phase comes from tick instants you provide with
+//!   `Metronome::set_interpolation_window`.
+//! - `spin_until` added, a portable (if power-hungry) way to wait out an `Idle`
+//!   when you have no way to sleep.
+//! - `Metronome::phase_jitter` added, to measure how unevenly frames are
+//!   spaced.
+//! - `Rate` now implements `From<(u32, u32)>`, so old tuple-based code can be
+//!   ported with a quick `.into()`.
+//! - `Metronome::last_now` added, returning the instant most recently read from
+//!   the `NowSource`.
+//! - `Metronome::prime` added, to start the tick grid without an immediate
+//!   tick.
+//! - `Metronome::set_phase_snap` added, to snap frame phases that are within a
+//!   hair of 0 or 1.
+//! - `Metronome::run_ticks` added, to run a given number of ticks immediately,
+//!   ignoring the clock, with timing identical to realtime.
+//! - `Reading::Tick` now has a `dt` field, giving the exact duration of that
+//!   tick. (Match it with `Reading::Tick{..}` if you don't care.)
+//! - `Rate::try_per_second` added, which returns a `RateError` instead of
+//!   panicking on a bad numerator or denominator.
+//! - `ScriptedNowSource` added (not available with `no_std`), which plays back
+//!   a predetermined list of times.
+//! - `Metronome::would_idle` added, to check whether a `sample` would just tell
+//!   you to idle, without disturbing anything.
+//! - `MetronomeIterator::coalesce_ticks` added, which turns each run of `Tick`s
+//!   into a single new `Reading::Ticks { count }`.
+//! - `Metronome::set_strict` added. In strict mode, a target framerate that is
+//!   wildly out of proportion to the tickrate produces a new
+//!   `Reading::FramerateMismatch`.
+//! - `Metronome::instant_of_tick_offset` added, returning the instant of a tick
+//!   some number of ticks before or after the most recent one.
+//! - `TemporalSample::rewound_by` added, with a default implementation that
+//!   returns `None`. Implement it for your own `TemporalSample`s to make
+//!   backwards-looking features work with them.
+//! - `Metronome::has_started` added, to tell whether the tick grid is running
+//!   yet.
+//! - `MetronomeIterator::precise_phase` added, giving the last frame's phase as
+//!   an `f64` with sub-nanosecond precision.
+//! - `Metronome::with_realtime` added, a shorthand for
+//!   `Metronome::new(RealtimeNowSource::new(), ...)`.
+//! - `Metronome::set_deterministic_phase` added, which computes frame phases
+//!   with integer arithmetic, so that they are bit-for-bit identical on every
+//!   platform.
+//! - `Mode` is now `#[non_exhaustive]`. `Mode::is_headless` and
+//!   `Mode::target_framerate` added, so you can ask about a mode without
+//!   matching on it.
+//! - `Metronome::set_frame_lead` added, to deliver `TargetFramesPerSecond`
+//!   frames a little ahead of time, to make up for rendering latency.
+//! - `Metronome::frame_bracket` added, returning the instants of the ticks on
+//!   either side of the most recent frame.
+//! - `Mode::Adaptive` added, which measures your loop's cadence for a second,
+//!   guesses the refresh rate, and then targets it.
+//! - Fixed: switching into `TargetFramesPerSecond` while ticks were already
+//!   running could panic.
+//! - `Metronome::pace_frame` and `Metronome::pace_frame_with` added, which
+//!   handle one pass of a simple loop, including sleeping through any `Idle`.
+//! - `Rate` now implements `Display` and `FromStr`, using the form `60000/1001`
+//!   (or just `30`).
+//! - `Rate::try_per_second_u64` added.
+//! - `Metronome::set_max_idle` added, to cap how long a single `Idle` can be.
+//! - `Reading::TickrateChanged` added, which comes right before the first tick
+//!   at a new tickrate.
+//! - `Metronome::next_wake_instant` added, giving the instant an idle would
+//!   end, for `sleep_until`-style APIs.
+//! - `MetronomeIterator::fixed_phase` added, giving the last frame's phase in
+//!   65536ths, computed without any floating point.
+//! - `Metronome::set_label` added, to name a `Metronome` in its `Debug` output.
+//! - `Metronome::anchor_to` added, to line the tick grid up with a shared
+//!   epoch, so that networked clients agree on when ticks happen.
+//! - `TemporalSample::checked_advanced_by` added. When the tick grid runs past
+//!   what your `TemporalSample` can represent, you now get a new
+//!   `Reading::EndOfTime` instead of wrapping or saturating.
+//! - `Metronome::add_output` added (not available with `no_std`), for rendering
+//!   frames to more than one target at different framerates from a single
+//!   stream of ticks. `Reading::Frame` has a new `output_id` field to tell them
+//!   apart; it is always 0 for the frames of the `Mode` you pass to `sample`.
+//! - `Metronome::frames_produced` added, counting every `Frame` produced over
+//!   the life of the `Metronome`.
+//! - New `debug_checks` feature flag. With it, debug builds assert that frame
+//!   phases never go backwards between the same two ticks, which catches a
+//!   misbehaving `NowSource`.
+//! - `Metronome::grid_error` added, returning how far ahead of or behind the
+//!   clock the tick grid is, as a new `GridOffset`.
+//! - `Metronome::snapshot` and `Metronome::restore` added, to save and restore
+//!   the timing state as a `MetronomeSnapshot`. New `serde` feature flag, which
+//!   makes snapshots serializable (when the instant type is, e.g. `Duration`).
+//! - In `TargetFramesPerSecond`, when the pattern of frames and ticks repeats
+//!   every 65536 frames or fewer, phases are now computed exactly from that
+//!   pattern, so they repeat exactly instead of drifting by a rounding error.
+//! - `Metronome::set_first_tick_delayed` added, to make the first tick happen a
+//!   whole tick after the first `sample`, instead of immediately.
+//! - `Metronome::dropped_frames` added, counting the frames
+//!   `TargetFramesPerSecond` had to skip because `sample` wasn't called often
+//!   enough.
+//! - `temporal_sample_via_ops!` added, which implements `TemporalSample` for a
+//!   type that already has `Add<Duration>` and `Sub` operators.
+//! - `Metronome::time_until_next_frame` added, giving the next frame deadline
+//!   on its own, for rendering on a separate thread from ticking.
+//! - Fixed: in `TargetFramesPerSecond`, with a framerate equal to the tickrate,
+//!   frames could drift off the ticks after a `set_tickrate`. They now always
+//!   land right on the ticks, at phase 1.
+//! - `Metronome::idle_fraction` added, giving the fraction of time `sample` has
+//!   told you to spend idle, as a measure of CPU headroom.
+//! - `merge_ticks` added, which samples two `Metronome`s and delivers their
+//!   ticks in chronological order, each tagged with a new `Which`.
+//! - `Metronome::set_skip_frame_on_ticks_lost` added, to skip the frame that
+//!   would otherwise follow a `TicksLost` at a phase of 1.
+//! - `Rate::ticks_in` added, giving the exact number of whole ticks that fit in
+//!   a `Duration`.
+//! - `Metronome::set_never_empty` added. With it, a batch that would be empty
+//!   produces a single new `Reading::NoOp` instead.
+//! - `Metronome::set_catch_up_policy` added. With
+//!   `CatchUpPolicy::StayAnchored`, the tick grid carries on with its old phase
+//!   after a `TicksLost`, instead of starting over from the current time.
+//! - `Metronome::simulate_frame_schedule` added (not available with `no_std`),
+//!   which lists the frame phases `TargetFramesPerSecond` would produce against
+//!   a perfectly regular clock, for auditing jitter.
+//! - New `hires_clock` feature flag, which adds `HiresNowSource`. It reads the
+//!   platform's high-resolution counter directly (`QueryPerformanceCounter` or
+//!   `CLOCK_MONOTONIC_RAW`), producing a new `HiresInstant`. Only on Windows,
+//!   Linux, Android, and Apple platforms.
+//! - A batch is now documented (and, in debug builds, asserted) to never
+//!   produce more than one `Frame` per output. Frames missed during a stall are
+//!   dropped, not rendered in a burst.
+//! - `Metronome::clone_with_source` added, which copies a `Metronome` onto a
+//!   different `NowSource` that produces the same type of instant.
+//! - `Metronome::report_actual_sleep` added. Tell it how long your sleeps
+//!   really took, and it will shorten future `Idle`s to make up for waking up
+//!   late.
+//! - `Metronome::sample_array` added, which collects a whole batch into a
+//!   fixed-size array, without allocating or keeping the `Metronome` borrowed.
+//!   `max_batch_len` gives a size that always fits.
+//! - `Metronome::set_phase_easing` added, to pass every frame phase through an
+//!   easing function before it is reported.
+//! - New `test-util` feature flag, which adds `validate_temporal_sample`, a
+//!   conformance check for your own `TemporalSample` implementations.
+//! - `Rate::per_minute`, `Rate::bpm`, and `Rate::bpm_subdivided` added, for
+//!   tick grids that follow a musical tempo.
+//! - `Metronome::future_frames_skipped` added, counting the times a frame was
+//!   held back for being (just barely) in the future.
+//! - `Metronome<RealtimeNowSource>` now implements `Default`, giving 60 ticks
+//!   per second with a `max_ticks_behind` of 3, for quick prototypes.
+//! - `Metronome::tick_at_instant` added, giving the number of the tick an
+//!   arbitrary instant falls within.
+//! - `Metronome::set_history_depth` and `Metronome::tick_history` added (not
+//!   available with `no_std`), to keep the instants of the last few ticks for
+//!   rollback netcode.
+//! - New `async` feature flag, which adds `Metronome::sample_async`. It awaits
+//!   a sleeper future of your choice instead of producing `Idle`, so it works
+//!   with any async runtime.
+//! - `Metronome::set_announce_first_tick` added. With it, a new
+//!   `Reading::FirstTick` comes right before the first tick, for one-time
+//!   setup.
+//! - `Metronome::reset` added, to forget all timing state and start over,
+//!   keeping the settings.
+//! - Added `Metronome::pause`, `resume` and `is_paused`. While paused, no ticks
+//!   are produced, and frames keep the phase they had when you paused.
+//! - Added the `rate!` macro, which makes a `const` `Rate` from a literal
+//!   fraction like `rate!(60000 / 1001)`, checking its limits at compile time.
+//! - Added `Metronome::set_render_delay`, which interpolates frames as if they
+//!   were a given time in the past, without delaying ticks.
+//! - Added `Reading::ClockStalled` and `Metronome::set_clock_stall_threshold`,
+//!   for noticing a `NowSource` that keeps reporting the same instant.
+//! - Added `Metronome::nearest_tick_instant`, which snaps an instant to the
+//!   closest tick on the grid.
+//! - Documented that a frame exactly on a tick has a phase of 1, and added
+//!   `Metronome::set_boundary_phase` to make it 0 instead, the start of the
+//!   next interval.
+//! - Added `CatchUpStyle` and `Metronome::set_catch_up_style`. With
+//!   `CatchUpStyle::SingleBigStep`, a run of ticks comes out as one `Tick` with
+//!   their combined `dt`.
+//! - Added `MetronomeIterator::peek`, which returns the next reading without
+//!   consuming it.
+//! - Added `Metronome::idle_range`, which gives a safe range to sleep for, for
+//!   sleep-then-spin pacing.
+//! - Added `Rate::phase_resolution`, the number of nanoseconds in one tick.
+//! - Added `Metronome::apply_clock_correction`, for when the clock is stepped
+//!   on purpose, e.g. by NTP.
+//! - Added `Metronome::set_backward_tolerance`. A backward jump of the clock
+//!   within the tolerance is absorbed, instead of producing `TimeWentBackwards`
+//!   and starting the tick grid over.
+//! - Added `Metronome::set_stats_window` and `windowed_stats`, which give tick
+//!   and frame rates and the idle fraction over a recent window of time (not
+//!   with `no_std`).
+//! - Added `Reading::SlowTick` and `Metronome::set_slow_tick_divisor`, for low-
+//!   frequency systems that tick in lockstep with the main tick grid.
+//! - Added `CallbackNowSource`, whose time only moves when you `push` a
+//!   timestamp to it, e.g. from a vsync callback.
+//! - Added `Rate::is_consistent`. In debug builds, comparing two `Rate`s
+//!   asserts that both are consistent.
+//! - Added `Metronome::render_once`, which returns the phase a frame rendered
+//!   right now would have, without disturbing anything.
+//! - Added `Metronome::set_recheck_idle`, which reads the clock again right
+//!   before producing an `Idle`, so that it covers only the time actually left.
+//! - Added `preview`, to see what readings a fresh `Metronome` would produce
+//!   between two instants.
+//! - `Reading::Frame` now has an `interpolating` field, which is `false` when
+//!   there were no two distinct ticks to interpolate between and `phase` was
+//!   simply forced to 1.
+//! - `Metronome::ticks_ahead` added. `UnlimitedFrames` now never delivers more
+//!   than one tick ahead of the current time, no matter how often it is
+//!   sampled.
+//! - New `testing` module, with the `test-util` feature: `run_test` checks a
+//!   timeline of `TestCmd`s against the exact readings it must produce, for
+//!   golden tests of your own timing.
+//! - `Metronome::hypothetical_phase` added, giving the phase a frame at some
+//!   other framerate would have, without disturbing anything.
+//! - Debug builds now catch a `Rate` made with `per_second_nonzero` that would
+//!   tick more than once per nanosecond.
+//! - `NowSource::sleep` is back, as an optional method that does nothing by
+//!   default. `Metronome::sample_and_idle` calls it to sleep through each
+//!   `Idle`. `RealtimeNowSource` sleeps the thread.
+//! - `Rate::reduced` added, giving the numerator and denominator in lowest
+//!   terms.
+//!
 //! ## Since 0.5.0
 //!
 //! - `ftvf` no longer depends on `std`. You can use the `no_std` feature flag
@@ -157,6 +407,11 @@ pub use metronome::*;
 mod time;
 #[doc(inline)]
 pub use time::*;
+mod interpolate;
+#[doc(inline)]
+pub use interpolate::*;
+#[cfg(all(feature="test-util", not(feature="no_std")))]
+pub mod testing;
 
 #[cfg(test)]
 mod test;
\ No newline at end of file
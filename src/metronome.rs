@@ -1,6 +1,10 @@
-use core::time::Duration;
+use core::{ops::RangeInclusive, time::Duration};
 
-use super::{NowSource, PreciseInstant, Rate, TemporalSample};
+use super::{NowSource, PreciseInstant, Rate, TemporalSample, gcd};
+#[cfg(not(feature="no_std"))]
+use super::{FakeNowSource, RealtimeNowSource};
+#[cfg(not(feature="no_std"))]
+use std::collections::VecDeque;
 
 /// The meat of the crate. Contains all state necessary to turn pure temporal
 /// chaos into an orderly stream of ticks and frames.
@@ -8,6 +12,7 @@ use super::{NowSource, PreciseInstant, Rate, TemporalSample};
 /// See the crate-level documentation for more information.
 #[derive(Debug, Clone)]
 pub struct Metronome<N: NowSource> {
+    label: Option<&'static str>,
     now_source: N,
     past_tick: Option<PreciseInstant<N::Instant>>,
     future_tick: Option<PreciseInstant<N::Instant>>,
@@ -15,6 +20,80 @@ pub struct Metronome<N: NowSource> {
     tickrate: Rate,
     last_framerate: Option<Rate>,
     max_ticks_behind: u32,
+    tick_number: u64,
+    frames_produced: u64,
+    dropped_frames: u64,
+    future_frames_skipped: u64,
+    /// The first instant `sample` saw, and all the `Idle` time since.
+    first_now: Option<N::Instant>,
+    idle_total: Duration,
+    interpolation_window: Option<(N::Instant, N::Instant)>,
+    /// `tick_number` and phase of the last frame.
+    last_frame_position: Option<(u64, f32)>,
+    /// Smallest and largest change in position between successive frames.
+    frame_step_range: Option<(f32, f32)>,
+    last_now: Option<N::Instant>,
+    /// The ticks on either side of the last frame.
+    frame_bracket: Option<(N::Instant, N::Instant)>,
+    /// When `Mode::Adaptive` started measuring, and when it last sampled.
+    cadence_start: Option<N::Instant>,
+    cadence_last: Option<N::Instant>,
+    /// How many sample intervals matched each of `REFRESH_RATES`.
+    cadence_votes: [u32; REFRESH_RATES.len()],
+    adaptive_framerate: Option<Rate>,
+    phase_snap: f32,
+    phase_easing: Option<fn(f32) -> f32>,
+    deterministic_phase: bool,
+    frame_lead: Duration,
+    render_delay: Duration,
+    max_idle: Option<Duration>,
+    recheck_idle: bool,
+    backward_tolerance: Duration,
+    clock_stall_threshold: Option<u32>,
+    /// How many samples in a row have seen the same `now` as the one before.
+    stalled_samples: u32,
+    /// The `now` the last `sample` saw. Unlike `last_now`, nothing else
+    /// touches it, so only samples count towards a stall.
+    last_sampled_now: Option<N::Instant>,
+    /// Running average of how much longer than asked your sleeps take.
+    sleep_overshoot: Duration,
+    /// Set by `set_tickrate`, cleared once the next tick announces it.
+    tickrate_changed: bool,
+    /// Set until the first tick since creation (or a reset) is announced.
+    first_tick_pending: bool,
+    announce_first_tick: bool,
+    /// Every this many ticks, a `SlowTick`. Zero for never.
+    slow_tick_divisor: u32,
+    /// When `pause` was called, and the phase at that instant.
+    paused_at: Option<N::Instant>,
+    paused_phase: Option<f32>,
+    strict: bool,
+    first_tick_delayed: bool,
+    skip_frame_on_ticks_lost: bool,
+    never_empty: bool,
+    /// The last (tickrate, framerate) pair we complained about.
+    reported_mismatch: Option<(Rate, Rate)>,
+    /// Where the tick grid starts from, if not from whenever it's needed.
+    anchor: Option<PreciseInstant<N::Instant>>,
+    catch_up_policy: CatchUpPolicy,
+    catch_up_style: CatchUpStyle,
+    boundary_phase: BoundaryPhase,
+    /// Where the grid picks up again after `TicksLost`, with `StayAnchored`.
+    resume_from: Option<PreciseInstant<N::Instant>>,
+    /// The rate and last frame of each output added with `add_output`.
+    #[cfg(not(feature="no_std"))]
+    outputs: Vec<(Rate, Option<PreciseInstant<N::Instant>>)>,
+    /// Recent ticks, oldest first. Only the last `history_depth` of them
+    /// count; older ones pile up, to at most twice that, before being
+    /// dropped all at once, so that each tick costs the same on average no
+    /// matter how deep the history is.
+    #[cfg(not(feature="no_std"))]
+    history: Vec<N::Instant>,
+    #[cfg(not(feature="no_std"))]
+    history_depth: usize,
+    /// Only there once `set_stats_window` has been called.
+    #[cfg(not(feature="no_std"))]
+    stats: Option<Stats<N::Instant>>,
 }
 
 /// Time handling information returned by a
@@ -22,17 +101,50 @@ pub struct Metronome<N: NowSource> {
 #[derive(Clone,Copy,Debug,PartialEq)]
 pub enum Reading {
     /// You should perform a logic tick.
-    Tick,
+    Tick {
+        /// How much time this tick represents: the exact time between this
+        /// tick and the next one, at the tickrate in effect when this tick
+        /// was produced. This is constant, except for a nanosecond of
+        /// wobble when the tickrate doesn't divide evenly into nanoseconds,
+        /// and except when you change the tickrate, or choose
+        /// [`CatchUpStyle::SingleBigStep`](enum.CatchUpStyle.html#variant.SingleBigStep).
+        dt: Duration,
+    },
+    /// You should perform this many logic ticks, all at once. Only produced
+    /// by [`MetronomeIterator::coalesce_ticks`](struct.MetronomeIterator.html#method.coalesce_ticks),
+    /// which turns each run of consecutive `Tick`s into one of these.
+    Ticks {
+        /// How many `Tick`s this stands in for. Always at least 1.
+        count: u32,
+    },
     /// You should render a frame.
     Frame {
         /// Indicates where in time we are. In the range 0 (previous tick) to
-        /// 1 (current tick), inclusive.
-        phase: f32
+        /// 1 (current tick), inclusive. A frame exactly on a tick has a
+        /// phase of 1, unless you've changed that with
+        /// [`Metronome::set_boundary_phase`](struct.Metronome.html#method.set_boundary_phase).
+        phase: f32,
+        /// `true` if `phase` was actually interpolated between two distinct
+        /// ticks. `false` if there was nothing to interpolate between (such
+        /// as on the very first frame, before there is a previous tick, or
+        /// in `OneFramePerTick` mode), in which case `phase` is simply 1.
+        interpolating: bool,
+        /// `true` if this frame lands exactly on the current tick. In
+        /// `TargetFramesPerSecond` mode, with a framerate that is a multiple
+        /// of the tickrate, this is how you find the frames that line up
+        /// with ticks.
+        on_tick_boundary: bool,
+        /// Which output this frame is for. Frames for the `Mode` you passed
+        /// to `sample` are output 0; any others are outputs you added with
+        /// [`Metronome::add_output`](struct.Metronome.html#method.add_output).
+        output_id: usize,
     },
     /// No `Tick` or `Frame` occurred this sample. If you call
     /// `std::thread::sleep(duration)` (or equivalent) and then sample again,
     /// you will have waited exactly long enough for the next `Tick` or `Frame`
-    /// to appear.
+    /// to appear. (Unless you've used
+    /// [`Metronome::set_max_idle`](struct.Metronome.html#method.set_max_idle),
+    /// in which case you might just get another `Idle`.)
     Idle {
         /// Indicates how long you need to sleep before it will be time for
         /// another tick or frame.
@@ -52,6 +164,58 @@ pub enum Reading {
     /// of ticks. This should be handled by showing some sort of warning, or
     /// ignored.
     TicksLost,
+    /// Only produced in strict mode (see
+    /// [`Metronome::set_strict`](struct.Metronome.html#method.set_strict)).
+    /// The target framerate is wildly out of proportion to the tickrate:
+    /// less than a hundredth of it, or more than a thousand times it. This is
+    /// almost certainly a configuration mistake. Produced at the start of the
+    /// first batch in which the mismatch is seen, and not again until the
+    /// tickrate or framerate changes.
+    FramerateMismatch,
+    /// The tickrate was changed with
+    /// [`Metronome::set_tickrate`](struct.Metronome.html#method.set_tickrate),
+    /// and the very next `Tick` is the first one at the new rate. (If that
+    /// tick happens in [`Metronome::run_ticks`](struct.Metronome.html#method.run_ticks)
+    /// instead, there's no announcement.)
+    TickrateChanged {
+        /// The new tickrate.
+        new: Rate,
+    },
+    /// The next tick would be later than your `TemporalSample` can represent
+    /// (see
+    /// [`TemporalSample::checked_advanced_by`](trait.TemporalSample.html#method.checked_advanced_by)).
+    /// There will be no more ticks, and every batch from now on will produce
+    /// this. You'll probably only see this with a `TemporalSample` of your
+    /// own that has a limited range.
+    EndOfTime,
+    /// Only produced if you call
+    /// [`Metronome::set_announce_first_tick`](struct.Metronome.html#method.set_announce_first_tick).
+    /// The very next `Tick` is the first one since this `Metronome` was
+    /// created, or since it was [`reset`](struct.Metronome.html#method.reset),
+    /// or since time went backwards and the tick grid started over. Do your
+    /// one-time setup here. (If that tick happens in
+    /// [`Metronome::run_ticks`](struct.Metronome.html#method.run_ticks)
+    /// instead, there's no announcement.)
+    FirstTick,
+    /// Only produced if you call
+    /// [`Metronome::set_slow_tick_divisor`](struct.Metronome.html#method.set_slow_tick_divisor).
+    /// You should perform a tick of your low-frequency systems. It comes
+    /// right after the `Tick` it belongs to. (If that tick happens in
+    /// [`Metronome::run_ticks`](struct.Metronome.html#method.run_ticks), or
+    /// is lost, there's no `SlowTick` for it.)
+    SlowTick,
+    /// Only produced if you call
+    /// [`Metronome::set_clock_stall_threshold`](struct.Metronome.html#method.set_clock_stall_threshold).
+    /// The [`NowSource`](trait.NowSource.html) has reported exactly the same
+    /// instant for that many samples in a row. Either it's too coarse for how
+    /// often you're sampling, or it has stopped. This should be handled by
+    /// showing some sort of warning, or ignored.
+    ClockStalled,
+    /// There was nothing at all to do this sample. Only produced if you've
+    /// called [`Metronome::set_never_empty`](struct.Metronome.html#method.set_never_empty),
+    /// in which case it stands in for an empty batch, so that every batch
+    /// produces at least one reading.
+    NoOp,
 }
 
 #[deprecated(since="0.6.0", note="use Reading instead")]
@@ -60,7 +224,11 @@ pub type Status = Reading;
 
 /// How ticks and frames should relate to one another in a given call to
 /// [`Metronome::sample`](struct.Metronome.html#method.sample).
+///
+/// New modes may be added in future versions. If you need to know something
+/// about a mode, try the helper methods before reaching for a `match`.
 #[derive(Clone,Copy,Debug,PartialEq)]
+#[non_exhaustive]
 pub enum Mode {
     /// No rendering is happening. Good for dedicated servers, logic test
     /// suites, minimized games, and other headless applications. Never yields
@@ -77,22 +245,288 @@ pub enum Mode {
     /// value if you *do* know the refresh rate. Frame phase will be very
     /// regular, especially if there is a simple relationship between tickrate
     /// and framerate.
+    ///
+    /// If the pattern of frames and ticks repeats often enough (every 65536
+    /// frames or fewer, e.g. every 24 frames for 30 TPS and 144 FPS), phases
+    /// are worked out exactly from that pattern, instead of from nanosecond
+    /// timings, so the sequence of phases repeats exactly, forever.
     TargetFramesPerSecond(Rate),
+    /// Render at the given target framerate, but never tick. This is for thin
+    /// clients, where some other party (such as a server) is doing all the
+    /// ticks, and you only render interpolated snapshots of its state. Phase
+    /// is computed against the tick instants you provide with
+    /// [`Metronome::set_interpolation_window`](struct.Metronome.html#method.set_interpolation_window).
+    /// Only ever yields `Frame` and `Idle`.
+    RenderOnly {
+        /// The framerate to aim for.
+        target_fps: Rate,
+    },
+    /// For when you don't know the refresh rate. Starts out as
+    /// `UnlimitedFrames`, while measuring how often you call `sample`. Once
+    /// a full second has gone by, picks the common refresh rate that best
+    /// matches the most common interval, and from then on acts as
+    /// `TargetFramesPerSecond` with that rate. (If nothing matched, it keeps
+    /// measuring.) You can see what it picked with
+    /// [`Metronome::adaptive_framerate`](struct.Metronome.html#method.adaptive_framerate).
+    ///
+    /// This only works if something else, such as vsync, is already pacing
+    /// your loop.
+    Adaptive,
 }
 
+/// The refresh rates `Mode::Adaptive` knows about.
+const REFRESH_RATES: [u32; 12] = [24, 30, 50, 60, 72, 75, 90, 100, 120, 144, 165, 240];
+
 impl Mode {
     #[allow(non_upper_case_globals)]
     #[deprecated(since="0.6.0", note="use OneFramePerTick instead")]
     #[doc(hidden)]
     pub const MaxOneFramePerTick: Mode = Mode::OneFramePerTick;
+    /// Returns `true` if this mode never produces frames.
+    pub fn is_headless(&self) -> bool {
+        matches!(self, Mode::TickOnly)
+    }
+    /// Returns the framerate this mode aims for, if it has one of its own.
+    /// (`OneFramePerTick` follows the tickrate, so this returns `None` for
+    /// it.)
+    pub fn target_framerate(&self) -> Option<Rate> {
+        match self {
+            Mode::TargetFramesPerSecond(rate) => Some(*rate),
+            Mode::RenderOnly { target_fps } => Some(*target_fps),
+            _ => None,
+        }
+    }
     fn needs_a_future(&self) -> bool {
+        matches!(self, Mode::UnlimitedFrames | Mode::TargetFramesPerSecond(_))
+    }
+    /// True if frames in this mode wait for their turn.
+    fn needs_pacing(&self) -> bool {
+        !matches!(self, Mode::TickOnly | Mode::UnlimitedFrames | Mode::Adaptive)
+    }
+    /// The rate frames are aligned to in this mode, if any.
+    fn framerate(&self, tickrate: Rate) -> Option<Rate> {
         match self {
-            Mode::UnlimitedFrames | Mode::TargetFramesPerSecond(_) => true,
-            _ => false,
+            Mode::OneFramePerTick => Some(tickrate),
+            _ => self.target_framerate(),
         }
     }
 }
 
+/// How far the tick grid is from the clock, as returned by
+/// [`Metronome::grid_error`](struct.Metronome.html#method.grid_error).
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum GridOffset {
+    /// The most recent tick is this far in the future. This is normal when
+    /// frames are being interpolated, since that needs a tick ahead of now.
+    Ahead(Duration),
+    /// The most recent tick is this far in the past.
+    Behind(Duration),
+}
+
+/// What happens to the tick grid after a `TicksLost`, as set by
+/// [`Metronome::set_catch_up_policy`](struct.Metronome.html#method.set_catch_up_policy).
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub enum CatchUpPolicy {
+    /// The grid starts over from the time of the `sample` that lost the
+    /// ticks, as if the `Metronome` were brand new (or from the
+    /// [`anchor_to`](struct.Metronome.html#method.anchor_to) epoch, if
+    /// there is one). This is the default.
+    #[default]
+    JumpToNow,
+    /// The grid carries on from where it was, skipping over the lost ticks,
+    /// so that ticks keep the same phase offset they always had.
+    StayAnchored,
+}
+
+/// Rates measured over a window of time, as returned by
+/// [`Metronome::windowed_stats`](struct.Metronome.html#method.windowed_stats).
+#[cfg(not(feature="no_std"))]
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct WindowedStats {
+    /// How many ticks there were per second.
+    pub ticks_per_second: f64,
+    /// How many frames there were per second, not counting extra outputs.
+    pub frames_per_second: f64,
+    /// The fraction of the time spent idle, from 0 to 1.
+    pub idle_fraction: f32,
+}
+
+/// When recent events happened, for `windowed_stats`.
+#[cfg(not(feature="no_std"))]
+#[derive(Clone,Debug)]
+struct Stats<I> {
+    window: Duration,
+    ticks: VecDeque<I>,
+    frames: VecDeque<I>,
+    idles: VecDeque<(I, Duration)>,
+    /// When the most recent `sample` was. Unlike `last_now`, nothing else
+    /// touches this, so peeking doesn't move the window.
+    sampled_at: Option<I>,
+}
+
+/// Forget the events at the front of `events` that are more than `window`
+/// older than the one at the back.
+#[cfg(not(feature="no_std"))]
+fn prune<T, I: TemporalSample>(events: &mut VecDeque<T>, at: impl Fn(&T) -> &I, window: Duration) {
+    let Some(newest) = events.back().map(|x| at(x).clone()) else { return };
+    while events.front().is_some_and(|x| newest.time_since(at(x)).is_some_and(|x| x > window)) {
+        events.pop_front();
+    }
+}
+
+/// How a batch delivers several ticks, as set by
+/// [`Metronome::set_catch_up_style`](struct.Metronome.html#method.set_catch_up_style).
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub enum CatchUpStyle {
+    /// Every tick is its own `Tick`, with its own `dt`. This is the default.
+    #[default]
+    Uniform,
+    /// Each run of consecutive ticks comes out as a single `Tick`, whose `dt`
+    /// is the sum of theirs. Cheaper to catch up with, if your logic can
+    /// take a variable step, but less accurate, and physics may not like it.
+    SingleBigStep,
+}
+
+/// Which phase a frame that lands exactly on a tick gets, as set by
+/// [`Metronome::set_boundary_phase`](struct.Metronome.html#method.set_boundary_phase).
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub enum BoundaryPhase {
+    /// The frame is the end of the interval leading up to the tick: it has a
+    /// phase of 1, and the tick is the current tick. This is the default.
+    #[default]
+    EndOfInterval,
+    /// The frame is the start of the interval after the tick: it has a phase
+    /// of 0, and the tick is the *previous* tick. The next tick is delivered
+    /// early to make this possible. Either way, `on_tick_boundary` is true.
+    StartOfInterval,
+}
+
+#[cfg(not(feature="no_std"))]
+impl Metronome<FakeNowSource> {
+    /// Returns the phase of every frame `TargetFramesPerSecond(framerate)`
+    /// would produce during the first `duration`, at the given tickrate, if
+    /// `sample` were called exactly when each frame was due. This runs the
+    /// real scheduling logic against a perfectly regular fake clock, so it's
+    /// a way to audit the frame schedule for jitter without any real timing
+    /// getting involved.
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// # use std::time::Duration;
+    /// let phases = Metronome::simulate_frame_schedule(
+    ///   Rate::per_second(30, 1), Rate::per_second(60, 1), Duration::from_secs(1),
+    /// );
+    /// assert_eq!(phases.len(), 60);
+    /// // The very first frame is on the very first tick; after that, every
+    /// // other frame lands halfway between ticks.
+    /// assert_eq!(&phases[..5], &[1.0, 0.5, 1.0, 0.5, 1.0]);
+    /// ```
+    pub fn simulate_frame_schedule(tickrate: Rate, framerate: Rate, duration: Duration) -> Vec<f32> {
+        let mode = Mode::TargetFramesPerSecond(framerate);
+        let mut metronome = Metronome::new(FakeNowSource::default(), tickrate, u32::MAX);
+        let mut phases = Vec::new();
+        let mut frame_at = Some(PreciseInstant::from(Duration::ZERO));
+        while let Some(frame) = frame_at.filter(|frame| frame.at < duration) {
+            metronome.now_source.now = frame.at;
+            for reading in metronome.sample(mode) {
+                if let Reading::Frame { phase, .. } = reading {
+                    phases.push(phase);
+                }
+            }
+            frame_at = frame.next(&framerate);
+        }
+        phases
+    }
+}
+
+/// Returns the readings a brand new `Metronome` at `tickrate` would produce
+/// in `mode`, if it were sampled at `from`, and then again at `to`. Only the
+/// readings from the second sample are returned. No ticks are ever lost, no
+/// matter how far apart `from` and `to` are. This runs the real logic
+/// against a [`FakeNowSource`](struct.FakeNowSource.html), so it's a quick
+/// way to see what `ftvf` would do in a given situation, or to write down
+/// the expected readings for a test. Not available with `no_std`.
+///
+/// ```rust
+/// # use ftvf::*;
+/// # use std::time::Duration;
+/// let readings = preview(Rate::per_second(10, 1), Mode::UnlimitedFrames,
+///                        Duration::ZERO, Duration::from_millis(150));
+/// assert_eq!(readings, &[
+///     Reading::Tick { dt: Duration::from_millis(100) },
+///     Reading::Tick { dt: Duration::from_millis(100) },
+///     Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
+/// ]);
+/// ```
+#[cfg(not(feature="no_std"))]
+pub fn preview(tickrate: Rate, mode: Mode, from: Duration, to: Duration) -> Vec<Reading> {
+    let mut metronome = Metronome::new(FakeNowSource { now: from }, tickrate, u32::MAX);
+    metronome.sample(mode).for_each(drop);
+    metronome.now_source.now = to;
+    metronome.sample(mode).collect()
+}
+
+/// The most readings a single batch can produce, given the `max_ticks_behind`
+/// you passed to [`Metronome::new`](struct.Metronome.html#method.new), the
+/// divisor passed to
+/// [`set_slow_tick_divisor`](struct.Metronome.html#method.set_slow_tick_divisor)
+/// (0 if you never called it), and the number of outputs you added with
+/// [`add_output`](struct.Metronome.html#method.add_output). That's up to
+/// `max_ticks_behind` ticks, each of which may be followed by a `SlowTick`,
+/// and then a `TickrateChanged`, a `FirstTick`, a `TicksLost`, a
+/// `TimeWentBackwards`, a `ClockStalled`, a `FramerateMismatch`, an
+/// `EndOfTime`, one `Frame` per output, and an `Idle`. This is a `const fn`,
+/// so you can use it to size
+/// [`sample_array`](struct.Metronome.html#method.sample_array):
+///
+/// ```rust
+/// # use ftvf::*;
+/// let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(30, 1), 5);
+/// metronome.set_slow_tick_divisor(10);
+/// let (readings, count) = metronome.sample_array::<{ max_batch_len(5, 10, 0) }>(Mode::OneFramePerTick);
+/// # let _ = (readings, count);
+/// ```
+pub const fn max_batch_len(max_ticks_behind: u32, slow_tick_divisor: u32, extra_outputs: usize) -> usize {
+    let ticks = max_ticks_behind as usize;
+    let slow_ticks = match slow_tick_divisor {
+        0 => 0,
+        divisor => ticks.div_ceil(divisor as usize),
+    };
+    ticks + slow_ticks + 9 + extra_outputs
+}
+
+#[cfg(not(feature="no_std"))]
+impl Metronome<RealtimeNowSource> {
+    /// Create a new `Metronome` that uses a
+    /// [`RealtimeNowSource`](struct.RealtimeNowSource.html), which is almost
+    /// certainly the one you want. See [`new`](#method.new) for what the
+    /// other parameters mean.
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// let mut metronome = Metronome::with_realtime(
+    ///   // want 30 ticks per 1 second
+    ///   Rate::per_second(30, 1),
+    ///   // accept being up to 5 ticks behind
+    ///   5,
+    /// );
+    /// ```
+    pub fn with_realtime(tickrate: Rate, max_ticks_behind: u32) -> Metronome<RealtimeNowSource> {
+        Metronome::new(RealtimeNowSource::new(), tickrate, max_ticks_behind)
+    }
+}
+
+/// A realtime `Metronome` ticking at **60 ticks per second**, that accepts
+/// being up to **3 ticks** behind. This is meant for quick prototypes. In a
+/// real game, pick your tickrate on purpose, with
+/// [`with_realtime`](struct.Metronome.html#method.with_realtime).
+#[cfg(not(feature="no_std"))]
+impl Default for Metronome<RealtimeNowSource> {
+    fn default() -> Metronome<RealtimeNowSource> {
+        Metronome::with_realtime(Rate::per_second(60, 1), 3)
+    }
+}
+
 impl<N: NowSource> Metronome<N> {
     /// Create a new `Metronome`, initialized with the given properties.
     /// - `now_source`: The [`NowSource`](trait.NowSource.html) to use.
@@ -120,34 +554,1265 @@ impl<N: NowSource> Metronome<N> {
             tickrate,
             last_framerate: None,
             max_ticks_behind,
+            tick_number: 0,
+            frames_produced: 0,
+            dropped_frames: 0,
+            future_frames_skipped: 0,
+            first_now: None,
+            idle_total: Duration::ZERO,
+            interpolation_window: None,
+            last_frame_position: None,
+            frame_step_range: None,
+            last_now: None,
+            frame_bracket: None,
+            cadence_start: None,
+            cadence_last: None,
+            cadence_votes: [0; REFRESH_RATES.len()],
+            adaptive_framerate: None,
+            phase_snap: 0.0,
+            phase_easing: None,
+            deterministic_phase: false,
+            frame_lead: Duration::ZERO,
+            render_delay: Duration::ZERO,
+            max_idle: None,
+            recheck_idle: false,
+            backward_tolerance: Duration::ZERO,
+            clock_stall_threshold: None,
+            stalled_samples: 0,
+            last_sampled_now: None,
+            sleep_overshoot: Duration::ZERO,
+            tickrate_changed: false,
+            first_tick_pending: true,
+            announce_first_tick: false,
+            slow_tick_divisor: 0,
+            paused_at: None,
+            paused_phase: None,
+            strict: false,
+            first_tick_delayed: false,
+            skip_frame_on_ticks_lost: false,
+            never_empty: false,
+            reported_mismatch: None,
+            label: None,
+            anchor: None,
+            catch_up_policy: CatchUpPolicy::JumpToNow,
+            catch_up_style: CatchUpStyle::Uniform,
+            boundary_phase: BoundaryPhase::EndOfInterval,
+            resume_from: None,
+            #[cfg(not(feature="no_std"))]
+            outputs: Vec::new(),
+            #[cfg(not(feature="no_std"))]
+            history: Vec::new(),
+            #[cfg(not(feature="no_std"))]
+            history_depth: 0,
+            #[cfg(not(feature="no_std"))]
+            stats: None,
         }
     }
     /// Call this from your logic loop, after checking for user input. Returns
     /// an `Iterator` of `Reading`s, describing how you should respond to the
     /// passage of time. See [`Reading`](enum.Reading.html) for info on what
     /// each reading means.
-    pub fn sample<'a>(&'a mut self, mode: Mode) -> impl Iterator<Item=Reading> + 'a {
-        let new_framerate = match mode {
-            Mode::TickOnly => None,
-            Mode::OneFramePerTick => Some(self.tickrate.clone()),
-            Mode::UnlimitedFrames => None,
-            Mode::TargetFramesPerSecond(rate) => Some(rate.clone()),
-        };
+    ///
+    /// It's an ordinary `Iterator`, so the standard adapters work on it. For
+    /// example, to log every reading while debugging, use `inspect`:
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// # let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(30, 1), 5);
+    /// let readings = metronome.sample(Mode::UnlimitedFrames)
+    ///     .inspect(|reading| eprintln!("{:?}", reading));
+    /// for reading in readings {
+    ///     // ...
+    /// # let _ = reading;
+    /// }
+    /// ```
+    ///
+    /// A batch never contains more than one `Frame` for each output. If
+    /// `sample` wasn't called for a while, frames that came due in the
+    /// meantime are not made up in a burst; only the latest one is rendered,
+    /// and the rest are counted by
+    /// [`dropped_frames`](#method.dropped_frames).
+    pub fn sample(&mut self, mode: Mode) -> MetronomeIterator<'_, N> {
+        // A stall is the `NowSource` repeating itself, not a small backward
+        // jump that was absorbed
+        let raw_now = self.now_source.now();
+        if self.last_sampled_now.as_ref() == Some(&raw_now) {
+            self.stalled_samples = self.stalled_samples.saturating_add(1);
+        } else {
+            self.stalled_samples = 0;
+        }
+        self.last_sampled_now = Some(raw_now.clone());
+        let now = self.absorb_backward_jump(raw_now);
+        let clock_stalled = self.clock_stall_threshold
+            .is_some_and(|threshold| self.stalled_samples == threshold.max(1));
+        self.last_now = Some(now.clone());
+        if self.first_now.is_none() {
+            self.first_now = Some(now.clone());
+        }
+        #[cfg(not(feature="no_std"))]
+        if let Some(stats) = self.stats.as_mut() {
+            stats.sampled_at = Some(now.clone());
+        }
+        if self.delays_first_tick() {
+            self.past_tick = Some(self.grid_start(&now));
+            self.future_tick = self.past_tick.clone();
+            self.last_frame = None;
+        }
+        if mode == Mode::Adaptive {
+            self.measure_cadence(&now);
+        }
+        let mode = self.effective_mode(mode);
+        let new_framerate = mode.framerate(self.tickrate);
         if new_framerate != self.last_framerate {
             self.last_framerate = new_framerate;
             self.last_frame = None;
         }
+        let mut readings = MetronomeIterator::new(self, mode, now);
+        readings.clock_stalled = clock_stalled;
+        readings
+    }
+    /// Like [`sample`](#method.sample), but collects the whole batch into an
+    /// array, and returns it along with how many readings there were. The
+    /// rest of the array is filled with `Reading::NoOp`. This allocates
+    /// nothing, and doesn't keep `self` borrowed, which is handy on embedded
+    /// targets. [`max_batch_len`](fn.max_batch_len.html) gives a size that
+    /// always fits.
+    ///
+    /// If the batch doesn't fit, this PANICS in debug builds. In release
+    /// builds, the readings that don't fit are discarded, just as if you'd
+    /// stopped iterating early.
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(30, 1), 5);
+    /// let (readings, count) = metronome.sample_array::<13>(Mode::OneFramePerTick);
+    /// for reading in &readings[.. count] {
+    ///     // ...
+    /// #   let _ = reading;
+    /// }
+    /// # assert_eq!(count, 2);
+    /// ```
+    pub fn sample_array<const LEN: usize>(&mut self, mode: Mode) -> ([Reading; LEN], usize) {
+        let mut readings = [Reading::NoOp; LEN];
+        let mut count = 0;
+        for reading in self.sample(mode) {
+            debug_assert!(count < LEN, "a batch had more than {} readings", LEN);
+            if count == LEN { break }
+            readings[count] = reading;
+            count += 1;
+        }
+        (readings, count)
+    }
+    /// Handle one whole pass of your loop: `sample`, pass every reading except
+    /// `Idle` to `handler`, and then, if there was an `Idle`, sleep for that
+    /// long. Returns how long it slept, if at all. This is for simple
+    /// programs that would rather not write the whole loop themselves:
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// # struct GameWorld {}
+    /// # impl GameWorld {
+    /// #   fn perform_tick(&mut self) {}
+    /// #   fn render(&mut self, _: f32) {}
+    /// #   fn should_quit(&mut self) -> bool { true }
+    /// # }
+    /// # let mut world = GameWorld{};
+    /// let mut metronome = Metronome::with_realtime(Rate::per_second(30, 1), 5);
+    /// while !world.should_quit() {
+    ///     metronome.pace_frame(Mode::OneFramePerTick, |reading| match reading {
+    ///         Reading::Tick{..} => world.perform_tick(),
+    ///         Reading::Frame{phase, ..} => world.render(phase),
+    ///         _ => (),
+    ///     });
+    /// }
+    /// ```
+    #[cfg(not(feature="no_std"))]
+    pub fn pace_frame(&mut self, mode: Mode, handler: impl FnMut(Reading)) -> Option<Duration> {
+        self.pace_frame_with(mode, handler, std::thread::sleep)
+    }
+    /// As [`pace_frame`](#method.pace_frame), but calls `sleep` to do the
+    /// sleeping. Available with `no_std`, as long as you have some way to
+    /// sleep.
+    pub fn pace_frame_with(&mut self, mode: Mode, mut handler: impl FnMut(Reading), sleep: impl FnOnce(Duration)) -> Option<Duration> {
+        let mut idle = None;
+        for reading in self.sample(mode) {
+            match reading {
+                Reading::Idle { duration } => idle = Some(duration),
+                x => handler(x),
+            }
+        }
+        if let Some(duration) = idle {
+            sleep(duration);
+        }
+        idle
+    }
+    /// As [`sample`](#method.sample), but every `Idle` is slept through, by
+    /// calling [`NowSource::sleep`](trait.NowSource.html#method.sleep), just
+    /// before it is yielded. `RealtimeNowSource` sleeps the thread; a source
+    /// that doesn't implement `sleep` does nothing, and you're right back to
+    /// the plain `sample` behavior.
+    pub fn sample_and_idle(&mut self, mode: Mode) -> MetronomeIterator<'_, N> {
+        let mut readings = self.sample(mode);
+        readings.sleep_on_idle = true;
+        readings
+    }
+    /// Returns the framerate `Mode::Adaptive` settled on, or `None` if it
+    /// hasn't settled yet.
+    pub fn adaptive_framerate(&self) -> Option<Rate> {
+        self.adaptive_framerate
+    }
+    /// The mode that `mode` actually acts like right now.
+    fn effective_mode(&self, mode: Mode) -> Mode {
+        match mode {
+            Mode::Adaptive => match self.adaptive_framerate {
+                Some(rate) => Mode::TargetFramesPerSecond(rate),
+                None => Mode::UnlimitedFrames,
+            },
+            x => x,
+        }
+    }
+    /// Count another `Mode::Adaptive` sample, and settle on a framerate if
+    /// it's time.
+    fn measure_cadence(&mut self, now: &N::Instant) {
+        if self.adaptive_framerate.is_some() { return }
+        if let Some(interval) = self.cadence_last.as_ref().and_then(|last| now.time_since(last)) {
+            let interval = interval.as_nanos() as f64;
+            // the nearest refresh rate, if it's within 5%
+            let nearest = REFRESH_RATES.iter().enumerate().map(|(n, &hz)| {
+                let period = 1_000_000_000.0 / hz as f64;
+                (n, (interval - period).abs() / period)
+            }).min_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((n, error)) = nearest {
+                if error < 0.05 { self.cadence_votes[n] += 1 }
+            }
+        }
+        self.cadence_last = Some(now.clone());
+        let start = self.cadence_start.get_or_insert_with(|| now.clone());
+        if now.time_since(start).is_some_and(|x| x >= Duration::from_secs(1)) {
+            let (best, &votes) = self.cadence_votes.iter().enumerate()
+                .max_by_key(|(_, votes)| **votes).unwrap();
+            if votes > 0 {
+                self.adaptive_framerate = Some(Rate::per_second(REFRESH_RATES[best], 1));
+            } else {
+                // Nothing recognizable. Start over.
+                self.cadence_start = None;
+            }
+        }
+    }
+    /// The async version of [`sample`](#method.sample). Whenever a `sample`
+    /// would produce nothing but an `Idle`, this awaits `sleeper(duration)`
+    /// instead, and then tries again, until there's something else to do.
+    /// The batch it returns never consists of just an `Idle`. `sleeper` can
+    /// be any async runtime's sleep function, e.g. `tokio::time::sleep`, so
+    /// `ftvf` doesn't depend on any particular runtime. Only available with
+    /// the `async` feature.
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// # async fn sleep(_: core::time::Duration) {}
+    /// # async fn game_loop() {
+    /// let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(30, 1), 5);
+    /// loop {
+    ///     for reading in metronome.sample_async(Mode::OneFramePerTick, sleep).await {
+    ///         // ...
+    /// #       let _ = reading;
+    ///     }
+    /// #   break
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature="async")]
+    pub async fn sample_async<F: core::future::Future>(&mut self, mode: Mode, mut sleeper: impl FnMut(Duration) -> F) -> MetronomeIterator<'_, N> {
+        while let Some(duration) = self.would_idle(mode) {
+            let now = self.last_now.clone().expect("would_idle should have read the clock");
+            self.remember_idle(&now, duration);
+            sleeper(duration).await;
+        }
+        self.sample(mode)
+    }
+    /// Peek at what `sample` would do right now. Returns `Some(duration)` if
+    /// a `sample` in the given `mode` would produce nothing but an `Idle` of
+    /// that duration, and `None` if it would produce anything else (or
+    /// nothing at all). Doesn't touch any tick or frame state, so it's safe
+    /// to call as often as you like before deciding whether to sleep.
+    ///
+    /// This does read the `NowSource`, so `last_now` is updated.
+    pub fn would_idle(&mut self, mode: Mode) -> Option<Duration> {
+        let now = self.read_clock();
+        let plan = self.plan(self.effective_mode(mode), &now);
+        self.last_now = Some(now);
+        if plan.time_went_backwards || plan.framerate_mismatch
+        || plan.tick.is_some() || plan.frame.is_some() {
+            None
+        } else {
+            plan.idle_for
+        }
+    }
+    /// As [`would_idle`](#method.would_idle), but returns the instant the
+    /// idle would end, instead of how long it would last. For APIs that want
+    /// a deadline, like `sleep_until`. This only reads the clock once, so
+    /// the deadline is exactly "now" plus the idle duration.
+    pub fn next_wake_instant(&mut self, mode: Mode) -> Option<N::Instant> {
+        let idle = self.would_idle(mode)?;
+        self.last_now.as_ref().map(|now| now.advanced_by(idle))
+    }
+    /// As [`would_idle`](#method.would_idle), but returns a range of
+    /// durations instead: the end is the very latest you can wake up without
+    /// missing the deadline (the same duration as the `Idle`), and the start
+    /// is that minus `margin`, the longest you should trust an imprecise OS
+    /// sleep with. Sleep for the start of the range, then spin (e.g. with
+    /// [`spin_until`](fn.spin_until.html)) the rest of the way.
+    pub fn idle_range(&mut self, mode: Mode, margin: Duration) -> Option<RangeInclusive<Duration>> {
+        let idle = self.would_idle(mode)?;
+        Some(idle.saturating_sub(margin) ..= idle)
+    }
+    /// Returns how long until the next frame is due in `mode`, ignoring
+    /// ticks, or zero if one is due right now. This is for when frames are
+    /// rendered on a separate thread from ticks, which wants to sleep until
+    /// its own deadline only. (In `OneFramePerTick`, frames are due on ticks
+    /// anyway.) Returns `None` in modes that don't wait between frames, i.e.
+    /// `TickOnly` and `UnlimitedFrames`.
+    ///
+    /// Like [`would_idle`](#method.would_idle), this doesn't disturb
+    /// anything, except for updating `last_now`.
+    pub fn time_until_next_frame(&mut self, mode: Mode) -> Option<Duration> {
+        let now = self.read_clock();
+        let mode = self.effective_mode(mode);
+        let plan = self.plan(mode, &now);
+        self.last_now = Some(now);
+        if !mode.needs_pacing() { None }
+        else if plan.frame.is_some() { Some(Duration::ZERO) }
+        else { plan.until_frame }
+    }
+    /// Returns the phase a frame rendered right now would have, for a one-off
+    /// render (like an admin screenshot) in a loop that otherwise doesn't
+    /// render, e.g. one in [`Mode::TickOnly`](enum.Mode.html#variant.TickOnly).
+    /// Phase snapping, easing, the render delay, and pausing all apply, just
+    /// as they would to a `Frame`. Nothing is disturbed: not the tick grid,
+    /// not the frame pacing, not even `last_now`.
+    ///
+    /// `TickOnly` doesn't deliver ticks early, so there, this is normally 1:
+    /// the state after the most recent tick is the newest there is.
+    pub fn render_once(&mut self) -> f32 {
+        let phase = match self.paused_phase {
+            Some(phase) => phase,
+            None => {
+                let now = self.read_clock();
+                match self.delayed_bracket(&PreciseInstant::from(now)) {
+                    Some((at, past_tick, future_tick))
+                        => phase_between(&at.at, &past_tick.at, &future_tick.at, self.deterministic_phase),
+                    None => 1.0,
+                }
+            },
+        };
+        self.ease_phase(snap_phase(phase, self.phase_snap))
+    }
+    /// Returns the phase a frame would have if you were targeting `framerate`
+    /// at `now`, without touching anything. Handy for a second recording at a
+    /// different framerate than the main display. The frame is the last one
+    /// at or before `now` on a grid of frames at `framerate` starting where
+    /// the tick grid first started, so framerates with a common multiple
+    /// agree where their frames coincide. Phase snapping, easing and the
+    /// boundary phase apply, as they would to a `Frame`.
+    ///
+    /// Ticks that haven't been delivered yet are extrapolated from the
+    /// current tick grid. Returns `None` if there hasn't been a tick yet, if
+    /// `now` is before the first sample, or if your `TemporalSample` can't go
+    /// back far enough.
+    pub fn hypothetical_phase(&self, now: N::Instant, framerate: Rate) -> Option<f32> {
+        let future_tick = self.future_tick.as_ref()?;
+        let frame = PreciseInstant::from(self.first_now.clone()?).last_tick_before(&now, &framerate);
+        if frame.at > now { return None }
+        let rate = &self.tickrate;
+        let before = match self.tick_offset_at(future_tick, &frame.at)? {
+            TickOffset::Forward(ticks) => future_tick.nth(ticks, rate)?,
+            TickOffset::Back(ticks) => future_tick.nth_back(ticks, rate)?,
+        };
+        let (past, future) = if self.frame_precedes(&frame, &before) {
+            (before.nth_back(1, rate)?, before)
+        } else {
+            let after = before.next(rate)?;
+            (before, after)
+        };
+        let phase = frames_per_schedule(rate, &framerate)
+            .and_then(|frames| scheduled_phase(&frame.at, &past.at, &future.at, frames))
+            .unwrap_or_else(|| phase_between(&frame.at, &past.at, &future.at, self.deterministic_phase));
+        Some(self.ease_phase(snap_phase(phase, self.phase_snap)))
+    }
+    /// Start the tick grid at the current time, as if a tick had just
+    /// happened, without actually delivering that tick. Normally, the very
+    /// first `sample` delivers a tick immediately; after priming, the first
+    /// tick comes one full tick later instead. (If you've called
+    /// [`anchor_to`](#method.anchor_to), the grid starts on the latest tick of
+    /// the anchored grid that isn't in the future, and the first tick comes
+    /// at the next one.) Call this right before you enter your game loop,
+    /// once all your loading is done:
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// # let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(30, 1), 5);
+    /// // ... load all the things ...
+    /// metronome.prime();
+    /// loop {
+    ///     for reading in metronome.sample(Mode::OneFramePerTick) {
+    ///         // ...
+    /// #       assert!(!matches!(reading, Reading::Tick{..}));
+    ///     }
+    /// #   break;
+    /// }
+    /// ```
+    pub fn prime(&mut self) {
+        let now = self.read_clock();
+        self.last_now = Some(now.clone());
+        self.past_tick = Some(self.grid_start(&now));
+        self.future_tick = self.past_tick.clone();
+        self.last_frame = None;
+    }
+    /// Run exactly `n` ticks, right now, as fast as possible, calling
+    /// `on_tick` for each one. The clock is ignored (except to start the tick
+    /// grid, if it hasn't been started yet). The tick grid advances exactly
+    /// as it would have in realtime with an idealized clock, so the timing is
+    /// bit-identical to a realtime run that hits the same ticks. This is
+    /// meant for headless fast-forwarding, such as AI training or skipping
+    /// through a replay.
+    ///
+    /// Note that the tick grid will now be ahead of the clock, so subsequent
+    /// `sample` calls will idle until real time catches up. If the tick grid
+    /// reaches the end of time, this stops early.
+    pub fn run_ticks(&mut self, n: u32, mut on_tick: impl FnMut()) {
+        for _ in 0 .. n {
+            let tick = match self.future_tick.as_ref() {
+                Some(future_tick) => match future_tick.next(&self.tickrate) {
+                    Some(x) => x,
+                    None => break,
+                },
+                None => {
+                    let now = self.read_clock();
+                    self.last_now = Some(now.clone());
+                    self.grid_start(&now)
+                },
+            };
+            self.past_tick = self.future_tick.take().or_else(|| Some(tick.clone()));
+            self.remember_tick(&tick.at);
+            self.future_tick = Some(tick);
+            self.tick_number += 1;
+            // nobody to announce a new tickrate (or the first tick) to
+            self.tickrate_changed = false;
+            self.first_tick_pending = false;
+            on_tick();
+        }
+    }
+    /// Keep the instants of the last `depth` ticks, for rollback netcode and
+    /// the like, which need to re-simulate (and re-interpolate) from a few
+    /// ticks back. Read them with [`tick_history`](#method.tick_history).
+    /// The default is 0, which keeps none and allocates nothing. Not
+    /// available with `no_std`.
+    #[cfg(not(feature="no_std"))]
+    pub fn set_history_depth(&mut self, depth: usize) {
+        if depth == 0 {
+            self.history = Vec::new();
+        } else if self.history.len() > depth {
+            self.history.drain(.. self.history.len() - depth);
+        }
+        self.history_depth = depth;
+    }
+    /// Returns the instants of the most recent ticks, up to the depth set by
+    /// [`set_history_depth`](#method.set_history_depth), oldest first. The
+    /// last one is the most recent tick. Not available with `no_std`.
+    #[cfg(not(feature="no_std"))]
+    pub fn tick_history(&self) -> &[N::Instant] {
+        &self.history[self.history.len().saturating_sub(self.history_depth) ..]
+    }
+    /// Add a tick to the history and the stats, if we're keeping them.
+    #[cfg(not(feature="no_std"))]
+    fn remember_tick(&mut self, at: &N::Instant) {
+        if self.history_depth > 0 {
+            if self.history.len() >= self.history_depth.saturating_mul(2) {
+                self.history.drain(.. self.history.len() - (self.history_depth - 1));
+            }
+            self.history.push(at.clone());
+        }
+        if let Some(stats) = self.stats.as_mut() {
+            stats.ticks.push_back(at.clone());
+            prune(&mut stats.ticks, |x| x, stats.window);
+        }
+    }
+    #[cfg(feature="no_std")]
+    fn remember_tick(&mut self, _at: &N::Instant) {}
+    /// Count a main frame in the stats, if we're keeping them.
+    #[cfg(not(feature="no_std"))]
+    fn remember_frame(&mut self, at: &N::Instant) {
+        if let Some(stats) = self.stats.as_mut() {
+            stats.frames.push_back(at.clone());
+            prune(&mut stats.frames, |x| x, stats.window);
+        }
+    }
+    #[cfg(feature="no_std")]
+    fn remember_frame(&mut self, _at: &N::Instant) {}
+    /// Count an `Idle` that starts at `at`.
+    #[cfg(not(feature="no_std"))]
+    fn remember_idle(&mut self, at: &N::Instant, duration: Duration) {
+        self.idle_total += duration;
+        if let Some(stats) = self.stats.as_mut() {
+            stats.idles.push_back((at.clone(), duration));
+            prune(&mut stats.idles, |(x, _)| x, stats.window);
+        }
+    }
+    #[cfg(feature="no_std")]
+    fn remember_idle(&mut self, _at: &N::Instant, duration: Duration) {
+        self.idle_total += duration;
+    }
+    /// Start keeping track of when ticks, frames, and idles happen, going
+    /// back `window`, so [`windowed_stats`](#method.windowed_stats) can tell
+    /// you about them. Until you call this, nothing is kept, and nothing is
+    /// allocated. Not available with `no_std`.
+    #[cfg(not(feature="no_std"))]
+    pub fn set_stats_window(&mut self, window: Duration) {
+        let stats = self.stats.get_or_insert_with(|| Stats {
+            window, ticks: VecDeque::new(), frames: VecDeque::new(), idles: VecDeque::new(),
+            sampled_at: None,
+        });
+        stats.window = window;
+    }
+    /// Returns the actual rates of ticks and frames, and the fraction of
+    /// time spent idle, over the last `window` before the most recent
+    /// `sample`. For a live performance display. `window` is limited to what
+    /// you passed to [`set_stats_window`](#method.set_stats_window), and to
+    /// how long ago the first `sample` was. Returns all zeroes if you haven't
+    /// called `set_stats_window`, or if no time has passed yet.
+    ///
+    /// Like [`idle_fraction`](#method.idle_fraction), this assumes you idle
+    /// for as long as each `Idle` says. Not available with `no_std`.
+    #[cfg(not(feature="no_std"))]
+    pub fn windowed_stats(&self, window: Duration) -> WindowedStats {
+        let Some(stats) = self.stats.as_ref() else {
+            return WindowedStats::default()
+        };
+        let (Some(sampled_at), Some(first_now)) = (stats.sampled_at.as_ref(), self.first_now.as_ref()) else {
+            return WindowedStats::default()
+        };
+        let window = window.min(stats.window).min(sampled_at.time_since(first_now).unwrap_or(Duration::ZERO));
+        if window.is_zero() { return WindowedStats::default() }
+        // Anything after `sampled_at`, like a tick delivered early, counts too
+        let recent = |at: &N::Instant| sampled_at.time_since(at).is_none_or(|x| x < window);
+        let seconds = window.as_secs_f64();
+        let idle: Duration = stats.idles.iter().filter(|(at, _)| recent(at)).map(|(_, x)| *x).sum();
+        WindowedStats {
+            ticks_per_second: stats.ticks.iter().filter(|x| recent(x)).count() as f64 / seconds,
+            frames_per_second: stats.frames.iter().filter(|x| recent(x)).count() as f64 / seconds,
+            idle_fraction: (idle.as_secs_f64() / seconds).min(1.0) as f32,
+        }
+    }
+    /// Line the tick grid up with `epoch`, so that every tick happens exactly
+    /// some whole number of ticks after it, no matter when you start
+    /// sampling. If every client in a networked game anchors to the same
+    /// epoch (e.g. the start of the match), their tick boundaries coincide.
+    ///
+    /// The grid restarts on the next `sample`, at the latest tick on the grid
+    /// that isn't in the future. If `epoch` hasn't come yet, that means
+    /// counting backwards from it, which needs
+    /// [`TemporalSample::rewound_by`](trait.TemporalSample.html#method.rewound_by);
+    /// if your `TemporalSample` can't do that, the grid starts unanchored.
+    /// The anchor sticks: whenever the grid restarts after
+    /// `TimeWentBackwards` or `TicksLost`, it restarts on the same grid. (If
+    /// you change the tickrate, the grid is recalculated from `epoch` at the
+    /// new rate.)
+    ///
+    /// This doesn't affect [`tick_number`](#method.tick_number).
+    pub fn anchor_to(&mut self, epoch: N::Instant) {
+        self.anchor = Some(PreciseInstant::from(epoch));
+        self.resume_from = None;
+        self.past_tick = None;
+        self.future_tick = None;
+        self.last_frame = None;
+    }
+    /// Where a new tick grid should start, if it started at `now`. Never
+    /// after `now`.
+    fn grid_start(&self, now: &N::Instant) -> PreciseInstant<N::Instant> {
+        let Some(anchor) = self.resume_from.as_ref().or(self.anchor.as_ref()) else {
+            return PreciseInstant::from(now.clone())
+        };
+        let mut start = match anchor.at.time_since(now) {
+            Some(until) => {
+                let ticks = until.as_nanos() / self.tickrate.duration_per.as_nanos();
+                match anchor.nth_back(ticks.try_into().unwrap_or(u32::MAX), &self.tickrate) {
+                    Some(x) => x,
+                    None => return PreciseInstant::from(now.clone()),
+                }
+            },
+            None => anchor.last_tick_before(now, &self.tickrate),
+        };
+        // Both of the above ignore residuals, and may land a little late.
+        while start.at > *now {
+            match start.nth_back(1, &self.tickrate) {
+                Some(x) => start = x,
+                None => return PreciseInstant::from(now.clone()),
+            }
+        }
+        start
+    }
+    /// Returns the number of ticks that have elapsed so far. This counts
+    /// every tick that has been returned as a `Tick`, and also estimates the
+    /// ticks that were skipped over whenever `TicksLost` occurred, so it
+    /// stays consistent with the wall clock.
+    pub fn tick_number(&self) -> u64 {
+        self.tick_number
+    }
+    /// Returns how many of the ticks already delivered are still in the
+    /// future, as of [`last_now`](#method.last_now). Modes that render
+    /// frames deliver the next tick a little early, so there's something to
+    /// interpolate towards; this is how many such ticks there are. In
+    /// `UnlimitedFrames` mode this is never more than 1, no matter how
+    /// often you sample, so `tick_number() - ticks_ahead()` is exactly the
+    /// number of ticks that are really due.
+    pub fn ticks_ahead(&self) -> u32 {
+        let Some(now) = self.last_now.as_ref() else { return 0 };
+        match (self.past_tick.as_ref(), self.future_tick.as_ref()) {
+            (Some(past), Some(future)) if past != future
+                => (past.at > *now) as u32 + (future.at > *now) as u32,
+            (_, Some(future)) => (future.at > *now) as u32,
+            _ => 0,
+        }
+    }
+    /// Reads the clock, and returns how far the most recent tick is from it.
+    /// In the steady state this is within one tick either way; if it's
+    /// further behind than that, you're not keeping up. Returns `None` if
+    /// there hasn't been a tick yet.
+    pub fn grid_error(&mut self) -> Option<GridOffset> {
+        let now = self.read_clock();
+        self.last_now = Some(now.clone());
+        let future_tick = self.future_tick.as_ref()?;
+        Some(match future_tick.at.time_since(&now) {
+            Some(ahead) => GridOffset::Ahead(ahead),
+            None => GridOffset::Behind(now.time_since(&future_tick.at).unwrap_or(Duration::ZERO)),
+        })
+    }
+    /// Returns the number of `Frame` readings produced so far, for every
+    /// output. Like `tick_number`, this is never reset, not even by
+    /// `TicksLost` or `TimeWentBackwards`, so the two together give you
+    /// averages over a whole session.
+    pub fn frames_produced(&self) -> u64 {
+        self.frames_produced
+    }
+    /// Returns how many frames `TargetFramesPerSecond` has skipped so far,
+    /// because you weren't calling `sample` often enough to render them all.
+    /// Only the latest frame that is due gets rendered; this counts the ones
+    /// in between. Never reset.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+    /// Returns how many times, in modes with a target framerate, a `sample`
+    /// produced no frame because the frame it picked turned out to be (a
+    /// hair) in the future. This is a diagnostic, for when you expected a
+    /// frame and didn't get one. It happens when the frame grid and the
+    /// clock disagree by less than a tick's worth of rounding.
+    pub fn future_frames_skipped(&self) -> u64 {
+        self.future_frames_skipped
+    }
+    /// Returns the fraction of time, from 0 to 1, that `sample` has told you
+    /// to spend idle, out of all the time since the first `sample`. The rest
+    /// is how much of the time your loop is busy, so this is a measure of
+    /// how much headroom you have: close to 1 means you could be doing a lot
+    /// more work, close to 0 means you're barely keeping up. Returns `None`
+    /// if no time has passed yet.
+    ///
+    /// This assumes you idle for as long as each `Idle` says, and is only
+    /// meaningful in modes that produce `Idle`.
+    pub fn idle_fraction(&self) -> Option<f32> {
+        let elapsed = self.last_now.as_ref()?.time_since(self.first_now.as_ref()?)?;
+        if elapsed.is_zero() { return None }
+        Some((self.idle_total.as_secs_f64() / elapsed.as_secs_f64()).min(1.0) as f32)
+    }
+    /// Returns `true` if the tick grid is running, i.e. there has been a tick
+    /// (or a [`prime`](#method.prime)) since the `Metronome` was created.
+    /// Returns `false` again after `TimeWentBackwards` or `TicksLost`, until
+    /// the grid restarts with the next tick.
+    pub fn has_started(&self) -> bool {
+        self.future_tick.is_some()
+    }
+    /// Returns how many ticks have elapsed since the point when
+    /// [`tick_number`](#method.tick_number) returned `past`, or zero if
+    /// `past` is in the future. Like `tick_number`, this includes ticks that
+    /// were lost, so a timer built on it ("ready in 90 ticks") stays
+    /// consistent with real time even when the game can't keep up.
+    pub fn elapsed_ticks_since(&self, past: u64) -> u64 {
+        self.tick_number.saturating_sub(past)
+    }
+    /// Returns the instant of the tick `offset` ticks away from the most
+    /// recent tick: 0 is the most recent tick itself, -3 is three ticks
+    /// before it, +3 is three ticks after. Handy for lag compensation, e.g.
+    /// to show what the world looked like a few ticks ago.
+    ///
+    /// Returns `None` if there hasn't been a tick yet, or if your
+    /// `TemporalSample` can't go back that far (see
+    /// [`TemporalSample::rewound_by`](trait.TemporalSample.html#method.rewound_by)).
+    ///
+    /// Past ticks are worked out from the current tickrate, not remembered.
+    /// If you changed the tickrate since then, the instants returned for
+    /// ticks before the change will be approximate.
+    pub fn instant_of_tick_offset(&self, offset: i32) -> Option<N::Instant> {
+        let future_tick = self.future_tick.as_ref()?;
+        let tick = if offset >= 0 {
+            future_tick.nth(offset as u32, &self.tickrate)?
+        } else {
+            future_tick.nth_back(offset.unsigned_abs(), &self.tickrate)?
+        };
+        Some(tick.at)
+    }
+    /// Returns the number of the tick whose interval contains `when`, i.e.
+    /// the last tick at or before it. Ticks are numbered the same way as
+    /// [`tick_number`](#method.tick_number): the most recent tick is number
+    /// `tick_number()`, the very first tick is number 1, and so on. This is
+    /// for working out which tick a timestamped event (such as a client's
+    /// action, on a server) belongs to.
+    ///
+    /// Returns `None` if there hasn't been a tick yet, if `when` is before
+    /// the first tick, or if your `TemporalSample` can't go back that far
+    /// (see [`TemporalSample::rewound_by`](trait.TemporalSample.html#method.rewound_by)).
+    ///
+    /// Like [`instant_of_tick_offset`](#method.instant_of_tick_offset), this
+    /// works from the current tickrate and assumes an unbroken tick grid. If
+    /// the tickrate has changed, or the grid started over after a
+    /// `TicksLost`, instants from before then map to approximate tick
+    /// numbers. (See [`set_catch_up_policy`](#method.set_catch_up_policy) to
+    /// keep the grid unbroken.)
+    pub fn tick_at_instant(&self, when: N::Instant) -> Option<u64> {
+        let future_tick = self.future_tick.as_ref()?;
+        match self.tick_offset_at(future_tick, &when)? {
+            TickOffset::Forward(ticks) => Some(self.tick_number + ticks as u64),
+            TickOffset::Back(ticks) => self.tick_number.checked_sub(ticks as u64).filter(|&x| x > 0),
+        }
+    }
+    /// Returns whichever tick on the grid is closest to `when`: the last one
+    /// at or before it, or the one after that. Exactly halfway between, it's
+    /// the earlier one. Handy for snapping timestamps to tick boundaries in
+    /// a debug timeline.
+    ///
+    /// Ticks before the first tick, or that haven't happened yet, are
+    /// extrapolated from the current tick grid, the same way as
+    /// [`instant_of_tick_offset`](#method.instant_of_tick_offset). Returns
+    /// `None` if there hasn't been a tick yet, or if your `TemporalSample`
+    /// can't go back far enough (see
+    /// [`TemporalSample::rewound_by`](trait.TemporalSample.html#method.rewound_by)).
+    pub fn nearest_tick_instant(&self, when: N::Instant) -> Option<N::Instant> {
+        let future_tick = self.future_tick.as_ref()?;
+        let rate = &self.tickrate;
+        let before = match self.tick_offset_at(future_tick, &when)? {
+            TickOffset::Forward(ticks) => future_tick.nth(ticks, rate)?,
+            TickOffset::Back(ticks) => future_tick.nth_back(ticks, rate)?,
+        };
+        let Some(after) = before.next(rate) else { return Some(before.at) };
+        let since_before = when.time_since(&before.at)?;
+        let until_after = after.at.time_since(&when)?;
+        Some(if until_after < since_before { after.at } else { before.at })
+    }
+    /// Where the last tick at or before `when` is, counting from
+    /// `future_tick`.
+    fn tick_offset_at(&self, future_tick: &PreciseInstant<N::Instant>, when: &N::Instant) -> Option<TickOffset> {
+        let rate = &self.tickrate;
+        if *when >= future_tick.at {
+            // `ticks_until` can overshoot by the residual, but never
+            // undershoots
+            let mut ticks = future_tick.ticks_until(when, rate);
+            while ticks > 0 && future_tick.nth(ticks, rate).is_none_or(|tick| tick.at > *when) {
+                ticks -= 1;
+            }
+            return Some(TickOffset::Forward(ticks))
+        }
+        let until = future_tick.at.time_since(when)?;
+        let mut ticks: u32 = until.as_nanos().div_ceil(rate.duration_per.as_nanos())
+            .try_into().ok()?;
+        while future_tick.nth_back(ticks, rate)?.at > *when {
+            ticks = ticks.checked_add(1)?;
+        }
+        while ticks > 1 && future_tick.nth_back(ticks - 1, rate)?.at <= *when {
+            ticks -= 1;
+        }
+        Some(TickOffset::Back(ticks))
+    }
+    /// Returns the instant most recently read from the `NowSource`, or `None`
+    /// if it has never been read. Useful for lining up `ftvf`'s idea of time
+    /// with other subsystems that read the same clock.
+    pub fn last_now(&self) -> Option<N::Instant> {
+        self.last_now.clone()
+    }
+    /// Returns the instants of the previous and current ticks, that is, the
+    /// ticks at phase 0 and phase 1, as of the most recent `Frame`. Handy for
+    /// things like motion blur, where you need to know when the shutter
+    /// opens and closes and not just where in between you are. Returns
+    /// `None` if the most recent frame didn't have two distinct ticks to sit
+    /// between (or if there hasn't been a frame yet).
+    pub fn frame_bracket(&self) -> Option<(N::Instant, N::Instant)> {
+        self.frame_bracket.clone()
+    }
+    /// Returns how much the frame phase jitters, or `None` if fewer than two
+    /// frames have been rendered. This is the spread between the smallest and
+    /// largest distance, in ticks, that has passed between two successive
+    /// frames. Perfectly evenly spaced frames have no jitter at all. If
+    /// `UnlimitedFrames` gives you a lot of jitter, consider using
+    /// `TargetFramesPerSecond` instead.
+    pub fn phase_jitter(&self) -> Option<f32> {
+        self.frame_step_range.map(|(min, max)| max - min)
+    }
+    /// Snap frame phases that are within `epsilon` of 0 or 1 to exactly those
+    /// values. This can stabilize renderers that snap interpolated positions
+    /// to whole pixels, which would otherwise flicker when a frame lands a
+    /// hair to one side of a tick. The default is 0, which never snaps.
+    pub fn set_phase_snap(&mut self, epsilon: f32) {
+        self.phase_snap = epsilon;
+    }
+    /// Pass every frame's `phase` through `easing` before reporting it, e.g.
+    /// to ease camera motion between ticks, consistently for everything that
+    /// interpolates. `easing` should map 0 to 0 and 1 to 1, and never
+    /// decrease in between; anything else will make interpolated motion go
+    /// backwards or jump at every tick. This applies after
+    /// [`set_phase_snap`](#method.set_phase_snap), and only to `phase`;
+    /// `precise_phase`, `fixed_phase`, and `phase_jitter` still see the
+    /// linear phase. The default is `None`, for linear phase.
+    pub fn set_phase_easing(&mut self, easing: Option<fn(f32) -> f32>) {
+        self.phase_easing = easing;
+    }
+    /// The given `phase`, eased, if we're easing.
+    fn ease_phase(&self, phase: f32) -> f32 {
+        match self.phase_easing {
+            Some(easing) => easing(phase),
+            None => phase,
+        }
+    }
+    /// Compute frame phases using only integer arithmetic, rounding to the
+    /// nearest 1/16777216th (2<sup>-24</sup>) of a tick. The conversion to
+    /// `f32` at the end is exact, so a given frame gets exactly the same
+    /// phase, down to the bit, on every platform. Turn this on if something
+    /// downstream of the phase (like a replay checksum) has to match
+    /// everywhere. The default is off.
+    pub fn set_deterministic_phase(&mut self, deterministic: bool) {
+        self.deterministic_phase = deterministic;
+    }
+    /// In `TargetFramesPerSecond` mode, produce each frame `lead` earlier
+    /// than its place on the frame grid. The frame's phase is still that of
+    /// its grid instant, so if `lead` is how long it takes your frame to go
+    /// from `Frame` reading to photons, what appears on screen will match the
+    /// time it appears. Ticks are delivered early as needed to make this
+    /// possible. The default is zero.
+    pub fn set_frame_lead(&mut self, lead: Duration) {
+        self.frame_lead = lead;
+    }
+    /// In `UnlimitedFrames` and `TargetFramesPerSecond` modes, compute each
+    /// frame's phase as if it were `delay` earlier. Ticks, and the pacing of
+    /// frames, are unaffected. This is the "interpolation buffer" that
+    /// networked games use to hide jitter: with a delay of one tick, a frame
+    /// right on the current tick gets the phase the previous tick had, so
+    /// you interpolate between the two ticks *before* the current one, and
+    /// must keep one more old state around. The default is zero.
+    ///
+    /// Needs a `TemporalSample` that implements
+    /// [`rewound_by`](trait.TemporalSample.html#method.rewound_by). Whenever
+    /// the delayed instant can't be represented, the delay is ignored.
+    pub fn set_render_delay(&mut self, delay: Duration) {
+        self.render_delay = delay;
+    }
+    /// Where a frame at `frame` should be interpolated, allowing for the
+    /// render delay: the delayed instant, and the two ticks on either side
+    /// of it. `None` if there aren't two different ticks yet.
+    fn delayed_bracket(&self, frame: &PreciseInstant<N::Instant>) -> Option<Bracket<N::Instant>> {
+        let (mut past, mut future) = match (self.past_tick.as_ref(), self.future_tick.as_ref()) {
+            (Some(past), Some(future)) if past != future => (past.clone(), future.clone()),
+            _ => return None,
+        };
+        let at = match frame.at.rewound_by(self.render_delay) {
+            Some(at) if !self.render_delay.is_zero() => PreciseInstant { at, residual: frame.residual },
+            _ => return Some((frame.clone(), past, future)),
+        };
+        if let Some(behind) = past.at.time_since(&at.at) {
+            // Jump most of the way back, never too far, then step the rest.
+            // A frame right on a tick goes at the end of the earlier
+            // interval, just like an undelayed one.
+            let ticks = behind.as_nanos() / self.tickrate.duration_per.as_nanos().max(1);
+            let ticks = ticks.saturating_sub(1).try_into().unwrap_or(u32::MAX);
+            if ticks > 0 {
+                if let (Some(earlier), Some(later)) = (past.nth_back(ticks, &self.tickrate), past.nth_back(ticks - 1, &self.tickrate)) {
+                    (past, future) = (earlier, later);
+                }
+            }
+            while self.frame_precedes(&at, &past) {
+                let Some(earlier) = past.nth_back(1, &self.tickrate) else { break };
+                future = core::mem::replace(&mut past, earlier);
+            }
+        }
+        Some((at, past, future))
+    }
+    /// Never report an `Idle` longer than `max`. If there's longer to wait
+    /// than that, the next `sample` will just report another `Idle` for the
+    /// rest. Use this if your loop needs to wake up every so often anyway,
+    /// e.g. to poll for input, even while ticks and frames are far apart.
+    /// The default is no limit.
+    pub fn set_max_idle(&mut self, max: Duration) {
+        self.max_idle = Some(max);
+    }
+    /// If `recheck` is true, read the clock again right before producing an
+    /// `Idle`, and shorten it by however much time passed since the `sample`
+    /// (say, because you did other work before getting around to the
+    /// readings). If the deadline has already passed, there's no `Idle` at
+    /// all. Without this, `Idle` is measured from the `sample`, which is
+    /// exact only if you iterate right away. The default is off.
+    pub fn set_recheck_idle(&mut self, recheck: bool) {
+        self.recheck_idle = recheck;
+    }
+    /// If the `NowSource` ever goes back in time by no more than `tolerance`
+    /// since the last time it was read, pretend it didn't: use the instant
+    /// from last time instead. A clock that wobbles backward a little (like
+    /// some cheap timers do) then causes no trouble at all, instead of
+    /// losing the tick grid and interpolation. Bigger jumps are handled as
+    /// usual, with a `TimeWentBackwards`. The default is zero.
+    pub fn set_backward_tolerance(&mut self, tolerance: Duration) {
+        self.backward_tolerance = tolerance;
+    }
+    /// Read the `NowSource`, absorbing backward jumps within
+    /// `backward_tolerance`.
+    fn read_clock(&mut self) -> N::Instant {
         let now = self.now_source.now();
-        MetronomeIterator::new(self, mode, now)
+        self.absorb_backward_jump(now)
+    }
+    /// If `now` is within `backward_tolerance` before `last_now`, returns
+    /// `last_now` instead.
+    fn absorb_backward_jump(&self, now: N::Instant) -> N::Instant {
+        match self.last_now.as_ref() {
+            Some(last_now) if now < *last_now
+                && last_now.time_since(&now).is_some_and(|x| x <= self.backward_tolerance)
+                => last_now.clone(),
+            _ => now,
+        }
+    }
+    /// Produce a
+    /// [`Reading::ClockStalled`](enum.Reading.html#variant.ClockStalled)
+    /// when `samples` samples in a row (at least one) have gotten exactly
+    /// the same instant from the `NowSource` as the sample before them. It's
+    /// produced once per stall, when the threshold is reached. The default
+    /// is off.
+    pub fn set_clock_stall_threshold(&mut self, samples: u32) {
+        self.clock_stall_threshold = Some(samples);
+    }
+    /// Tell the `Metronome` how long you actually slept, when you were asked
+    /// to sleep for `requested` by an `Idle`. Most sleep functions wake up a
+    /// little late, and if that happens on every frame, it adds up to a
+    /// framerate consistently a hair below target. The `Metronome` keeps a
+    /// running average of how late you wake up, and shortens future `Idle`s
+    /// by that much, so that you wake up on time. (An `Idle` shorter than
+    /// that is skipped entirely, since sleeping would only make you late.)
+    ///
+    /// If you never call this, no compensation happens.
+    pub fn report_actual_sleep(&mut self, requested: Duration, actual: Duration) {
+        let overshoot = actual.saturating_sub(requested).as_nanos();
+        let average = (self.sleep_overshoot.as_nanos() * 7 + overshoot) / 8;
+        self.sleep_overshoot = Duration::from_nanos(average.try_into().unwrap_or(u64::MAX));
+    }
+    /// If `delayed` is true, the very first tick happens one whole tick after
+    /// the first `sample`, instead of immediately, as if you had called
+    /// [`prime`](#method.prime) just before it. This is for when the first
+    /// `sample` marks the moment the game starts (such as a server
+    /// handshake), and the first tick should represent a whole tick's worth
+    /// of time after that. This has no effect once the first tick has
+    /// happened. The default is off.
+    pub fn set_first_tick_delayed(&mut self, delayed: bool) {
+        self.first_tick_delayed = delayed;
+    }
+    /// If `skip` is true, a batch that produces `TicksLost` produces no
+    /// frames after it. Normally, the frame after a `TicksLost` has no ticks
+    /// to interpolate between and falls back to a phase of 1, which can make
+    /// interpolated motion visibly pop; with this set, you don't render again
+    /// until the next `sample` has restarted the ticks. The default is off.
+    pub fn set_skip_frame_on_ticks_lost(&mut self, skip: bool) {
+        self.skip_frame_on_ticks_lost = skip;
+    }
+    /// If `never_empty` is true, a batch that would otherwise produce no
+    /// readings at all produces a single
+    /// [`Reading::NoOp`](enum.Reading.html#variant.NoOp) instead. This is
+    /// for drivers that pump a state machine once per reading, and would
+    /// rather not special-case an empty batch. The default is off.
+    pub fn set_never_empty(&mut self, never_empty: bool) {
+        self.never_empty = never_empty;
+    }
+    /// Choose what happens to the tick grid after a `TicksLost`. See
+    /// [`CatchUpPolicy`](enum.CatchUpPolicy.html). The default is
+    /// `JumpToNow`.
+    pub fn set_catch_up_policy(&mut self, policy: CatchUpPolicy) {
+        self.catch_up_policy = policy;
+    }
+    /// Choose whether a batch with several ticks in it delivers them one by
+    /// one, or as one big step. See [`CatchUpStyle`](enum.CatchUpStyle.html).
+    /// Either way, the `dt`s in a batch add up to the same total, and
+    /// [`tick_number`](#method.tick_number) counts every tick. The default
+    /// is `Uniform`.
+    pub fn set_catch_up_style(&mut self, style: CatchUpStyle) {
+        self.catch_up_style = style;
+    }
+    /// Choose which phase a frame gets when it lands exactly on a tick: 1,
+    /// at the end of the interval before the tick, or 0, at the start of the
+    /// interval after it. The two show the same instant, but they pick
+    /// different pairs of states to interpolate between, which matters if
+    /// you trigger effects on the state itself. See
+    /// [`BoundaryPhase`](enum.BoundaryPhase.html). Only affects
+    /// `UnlimitedFrames` and `TargetFramesPerSecond` modes. The default is
+    /// `EndOfInterval`.
+    pub fn set_boundary_phase(&mut self, boundary: BoundaryPhase) {
+        self.boundary_phase = boundary;
+    }
+    /// True if a frame at `frame` belongs before the tick at `tick`, rather
+    /// than after it.
+    fn frame_precedes<I: TemporalSample>(&self, frame: &PreciseInstant<I>, tick: &PreciseInstant<I>) -> bool {
+        match self.boundary_phase {
+            BoundaryPhase::EndOfInterval => frame <= tick,
+            BoundaryPhase::StartOfInterval => frame < tick,
+        }
+    }
+    /// True if the next `sample` should start the tick grid without ticking.
+    fn delays_first_tick(&self) -> bool {
+        self.first_tick_delayed && self.tick_number == 0 && self.future_tick.is_none()
+    }
+    /// If `announce` is true, the first tick since this `Metronome` was
+    /// created (or [`reset`](#method.reset), or since time went backwards)
+    /// is preceded by a
+    /// [`Reading::FirstTick`](enum.Reading.html#variant.FirstTick), so you
+    /// can do one-time setup right on that tick without keeping track
+    /// yourself. The default is off.
+    pub fn set_announce_first_tick(&mut self, announce: bool) {
+        self.announce_first_tick = announce;
+    }
+    /// Follow every `divisor`th tick with a
+    /// [`Reading::SlowTick`](enum.Reading.html#variant.SlowTick), for systems
+    /// (like AI planning, or autosave) that run much less often than the
+    /// main logic, but should stay in lockstep with it. Ticks are counted by
+    /// [`tick_number`](#method.tick_number), so with a divisor of 10, slow
+    /// ticks follow ticks 10, 20, 30, and so on. Pass 0 to turn this back
+    /// off. The default is off.
+    pub fn set_slow_tick_divisor(&mut self, divisor: u32) {
+        self.slow_tick_divisor = divisor;
+    }
+    /// Turn strict mode on or off. In strict mode, `sample` checks your
+    /// configuration for obvious mistakes, and produces a
+    /// [`Reading::FramerateMismatch`](enum.Reading.html#variant.FramerateMismatch)
+    /// if it finds one. Nothing else changes. This is meant for catching
+    /// mistakes during development; `set_strict(cfg!(debug_assertions))` is
+    /// a good way to call it. The default is off.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+    /// Add another output for frames, with its own framerate, sharing the
+    /// same ticks. This is for rendering to more than one target, e.g. a main
+    /// window and a preview pane with different refresh rates, without
+    /// running the game logic twice. Returns the `output_id` that this
+    /// output's `Frame`s will have. (The frames of the `Mode` you pass to
+    /// `sample` are always output 0.)
+    ///
+    /// Extra outputs are scheduled as if by `TargetFramesPerSecond`, and are
+    /// interleaved with the other frames in order. They only produce frames
+    /// in modes that produce both ticks and frames, i.e. not in `TickOnly`
+    /// or `RenderOnly`. Not available with `no_std`.
+    #[cfg(not(feature="no_std"))]
+    pub fn add_output(&mut self, rate: Rate) -> usize {
+        self.outputs.push((rate, None));
+        self.outputs.len()
+    }
+    /// The frames each extra output is due to render, in order, and how long
+    /// until the next one after that.
+    #[cfg(not(feature="no_std"))]
+    fn extra_frames(&self, mode: Mode, frame_now: &N::Instant, forget: bool) -> (ExtraFrames<N::Instant>, Option<Duration>) {
+        if !matches!(mode, Mode::OneFramePerTick | Mode::UnlimitedFrames | Mode::TargetFramesPerSecond(_)) {
+            return (Vec::new(), None)
+        }
+        let mut frames = Vec::new();
+        let mut idle_for: Option<Duration> = None;
+        for (index, (rate, last_frame)) in self.outputs.iter().enumerate() {
+            let last_frame = if forget { None } else { last_frame.as_ref() };
+            let frame = match last_frame {
+                Some(last_frame) => last_frame.last_tick_before(frame_now, rate),
+                None => PreciseInstant::from(frame_now.clone()),
+            };
+            let frame = if last_frame == Some(&frame) { None } else { Some(frame) };
+            if let Some(next) = frame.as_ref().or(last_frame).and_then(|x| x.next(rate)) {
+                if let Some(until) = next.at.time_since(frame_now) {
+                    idle_for = Some(idle_for.map_or(until, |x| x.min(until)));
+                }
+            }
+            if let Some(frame) = frame {
+                frames.push((index + 1, frame));
+            }
+        }
+        frames.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(core::cmp::Ordering::Equal));
+        (frames, idle_for)
+    }
+    /// Forget all timing state, as if this `Metronome` had just been created.
+    /// The next `sample` starts a fresh tick grid, and the counters (like
+    /// [`tick_number`](#method.tick_number) and
+    /// [`frames_produced`](#method.frames_produced)) start over from zero.
+    /// The tickrate, the settings made with the `set_*` methods, the
+    /// [`anchor_to`](#method.anchor_to) epoch, and any extra outputs are
+    /// kept. Use this when starting a new level or match.
+    pub fn reset(&mut self) {
+        self.past_tick = None;
+        self.future_tick = None;
+        self.forget_frames();
+        self.last_framerate = None;
+        self.tick_number = 0;
+        self.frames_produced = 0;
+        self.dropped_frames = 0;
+        self.future_frames_skipped = 0;
+        self.first_now = None;
+        self.idle_total = Duration::ZERO;
+        self.last_frame_position = None;
+        self.frame_step_range = None;
+        self.last_now = None;
+        self.stalled_samples = 0;
+        self.last_sampled_now = None;
+        self.frame_bracket = None;
+        self.tickrate_changed = false;
+        self.first_tick_pending = true;
+        self.reported_mismatch = None;
+        self.resume_from = None;
+        self.paused_at = None;
+        self.paused_phase = None;
+        #[cfg(not(feature="no_std"))]
+        self.history.clear();
+        #[cfg(not(feature="no_std"))]
+        if let Some(stats) = self.stats.as_mut() {
+            stats.ticks.clear();
+            stats.frames.clear();
+            stats.idles.clear();
+            stats.sampled_at = None;
+        }
+    }
+    /// Pause the game. Until you call [`resume`](#method.resume), `sample`
+    /// produces no ticks, and every `Frame` has the phase that a frame
+    /// rendered right now would have had, so the picture holds still while
+    /// you draw a pause menu over it. Frames are still paced as usual. Does
+    /// nothing if already paused.
+    pub fn pause(&mut self) {
+        if self.paused_at.is_some() { return }
+        let now = self.read_clock();
+        let phase = match self.delayed_bracket(&PreciseInstant::from(now.clone())) {
+            Some((at, past_tick, future_tick)) => {
+                phase_between(&at.at, &past_tick.at, &future_tick.at, self.deterministic_phase)
+            },
+            None => 1.0,
+        };
+        self.paused_at = Some(now);
+        self.paused_phase = Some(phase);
+    }
+    /// Undo a [`pause`](#method.pause). The tick grid is pushed back by
+    /// however long the pause lasted, so ticks pick up exactly where they
+    /// left off, without a burst of catch-up ticks or a jump in phase. Does
+    /// nothing if not paused.
+    pub fn resume(&mut self) {
+        let Some(paused_at) = self.paused_at.take() else { return };
+        self.paused_phase = None;
+        let now = self.read_clock();
+        let Some(paused_for) = now.time_since(&paused_at) else { return };
+        for tick in [&mut self.past_tick, &mut self.future_tick, &mut self.resume_from] {
+            if let Some(tick) = tick.as_mut() {
+                tick.at.advance_by(paused_for);
+            }
+        }
+    }
+    /// Tell the `Metronome` that the clock has just been stepped by `delta`:
+    /// backward if `backward` is true, forward otherwise. (For instance,
+    /// because it's synced to NTP.) Every instant the `Metronome` remembers,
+    /// including the tick grid, is shifted by the same amount, so ticks and
+    /// frames carry on as if nothing happened: a forward step doesn't
+    /// produce a burst of catch-up ticks, and a backward one doesn't produce
+    /// a `TimeWentBackwards`.
+    ///
+    /// The [`anchor_to`](#method.anchor_to) epoch and the
+    /// [`set_interpolation_window`](#method.set_interpolation_window) window
+    /// are yours, so they aren't touched. Backward steps need a
+    /// `TemporalSample` that implements
+    /// [`rewound_by`](trait.TemporalSample.html#method.rewound_by); anything
+    /// that can't be shifted is forgotten, and if that's the tick grid, it
+    /// starts over.
+    pub fn apply_clock_correction(&mut self, delta: Duration, backward: bool) {
+        let shift = |at: &N::Instant| if backward { at.rewound_by(delta) } else { at.checked_advanced_by(delta) };
+        let shift_tick = |tick: &mut Option<PreciseInstant<N::Instant>>| {
+            *tick = tick.take().and_then(|tick| Some(PreciseInstant { at: shift(&tick.at)?, residual: tick.residual }));
+        };
+        let (had_past, had_future) = (self.past_tick.is_some(), self.future_tick.is_some());
+        shift_tick(&mut self.past_tick);
+        shift_tick(&mut self.future_tick);
+        if self.past_tick.is_some() != had_past || self.future_tick.is_some() != had_future {
+            self.past_tick = None;
+            self.future_tick = None;
+        }
+        shift_tick(&mut self.last_frame);
+        shift_tick(&mut self.resume_from);
+        #[cfg(not(feature="no_std"))]
+        for output in self.outputs.iter_mut() {
+            shift_tick(&mut output.1);
+        }
+        for at in [&mut self.first_now, &mut self.last_now, &mut self.last_sampled_now, &mut self.paused_at, &mut self.cadence_start, &mut self.cadence_last] {
+            *at = at.as_ref().and_then(shift);
+        }
+        self.frame_bracket = self.frame_bracket.as_ref()
+            .and_then(|(past, future)| Some((shift(past)?, shift(future)?)));
+        #[cfg(not(feature="no_std"))]
+        {
+            let history = core::mem::take(&mut self.history);
+            self.history = history.iter().filter_map(shift).collect();
+            if let Some(stats) = self.stats.as_mut() {
+                stats.ticks = stats.ticks.iter().filter_map(shift).collect();
+                stats.frames = stats.frames.iter().filter_map(shift).collect();
+                stats.idles = stats.idles.iter()
+                    .filter_map(|(at, duration)| Some((shift(at)?, *duration))).collect();
+                stats.sampled_at = stats.sampled_at.as_ref().and_then(shift);
+            }
+        }
+    }
+    /// Returns true between a [`pause`](#method.pause) and the matching
+    /// [`resume`](#method.resume).
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+    /// Forget the last frame of every output.
+    fn forget_frames(&mut self) {
+        self.last_frame = None;
+        #[cfg(not(feature="no_std"))]
+        for output in self.outputs.iter_mut() {
+            output.1 = None;
+        }
+    }
+    /// Give this `Metronome` a name, which shows up in its `Debug` output.
+    /// Purely a diagnostic aid, for telling several `Metronome`s apart when
+    /// their output is interleaved in a log.
+    pub fn set_label(&mut self, label: &'static str) {
+        self.label = Some(label);
+    }
+    /// Returns the name given to [`set_label`](#method.set_label), if any.
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+    /// Provide the instants of the two ticks that
+    /// [`Mode::RenderOnly`](enum.Mode.html#variant.RenderOnly) frames should
+    /// be interpolated between: `prev` is phase 0, `next` is phase 1. Frames
+    /// outside the window are clamped to it. Until you call this, `RenderOnly`
+    /// frames have a phase of 1.
+    pub fn set_interpolation_window(&mut self, prev: N::Instant, next: N::Instant) {
+        self.interpolation_window = Some((prev, next));
     }
     /// Dynamically change the tickrate. You can call this at any time and it
     /// will take effect after the current tick. If you call this from within
     /// a loop over an iterator returned by `sample`, you should `break` out of
     /// the loop, because it does not currently detect the new tick rate
     /// mid-loop.
+    ///
+    /// Frame phase is always computed from the actual instants of the two
+    /// ticks on either side of the frame, so a frame that falls between the
+    /// last old-rate tick and the first new-rate tick gets a phase that
+    /// reflects the real gap between them.
+    ///
+    /// The first tick at the new rate is preceded by a
+    /// [`Reading::TickrateChanged`](enum.Reading.html#variant.TickrateChanged).
     pub fn set_tickrate(&mut self, new_rate: Rate) {
         if self.tickrate != new_rate {
             self.tickrate = new_rate;
+            self.tickrate_changed = true;
+            self.resume_from = None;
             if let Some(past_tick) = self.past_tick.as_mut() {
                 past_tick.forget_residual();
             }
@@ -156,89 +1821,412 @@ impl<N: NowSource> Metronome<N> {
             }
         }
     }
-}
-
-/// Returned by [`Metronome::sample`](struct.Metronome.html#method.sample). See
-/// that method's documentation.
-
-pub struct MetronomeIterator<'a, N: NowSource> {
-    metronome: &'a mut Metronome<N>,
-    now: N::Instant,
-    mode: Mode,
-    tick: Option<PreciseInstant<N::Instant>>,
-    frame: Option<PreciseInstant<N::Instant>>,
-    idle_for: Option<Duration>,
-    time_went_backwards: bool,
-    ticks_given: u32,
-}
-
-impl<N: NowSource> MetronomeIterator<'_, N> {
-    fn new(metronome: &mut Metronome<N>, mode: Mode, now: N::Instant) -> MetronomeIterator<'_, N> {
-        let mut time_went_backwards = false;
-        if let Some(past_tick) = metronome.past_tick.as_ref() {
-            if now < past_tick.at {
-                time_went_backwards = true;
-                metronome.past_tick = None;
-                metronome.future_tick = None;
-                metronome.last_frame = None;
+    /// Work out what a `sample` at `now` would do, without changing
+    /// anything.
+    fn plan(&self, mode: Mode, now: &N::Instant) -> Plan<N::Instant> {
+        let time_went_backwards = self.past_tick.as_ref()
+            .map(|past_tick| *now < past_tick.at).unwrap_or(false);
+        // What `sample` would have left of our state by the time it got here
+        let primed = if self.delays_first_tick() {
+            Some(self.grid_start(now))
+        } else { None };
+        let (future_tick, last_frame) = if let Some(primed) = primed.as_ref() {
+            (Some(primed), None)
+        } else if time_went_backwards {
+            (None, None)
+        } else if mode.framerate(self.tickrate) != self.last_framerate {
+            (self.future_tick.as_ref(), None)
+        } else {
+            (self.future_tick.as_ref(), self.last_frame.as_ref())
+        };
+        let tick = if let Some(future_tick) = future_tick {
+            match future_tick.next(&self.tickrate) {
+                Some(x) => x,
+                None => return Plan {
+                    time_went_backwards, framerate_mismatch: false, end_of_time: true,
+                    tick: None, frame: None, idle_for: None, until_frame: None, dropped_frames: 0,
+                    future_frame: false,
+                    #[cfg(not(feature="no_std"))]
+                    extra_frames: Vec::new(),
+                },
             }
-        }
-        let tick = if let Some(future_tick) = metronome.future_tick.as_ref() {
-            future_tick.next(&metronome.tickrate)
         } else {
-            PreciseInstant::from(now.clone())
+            self.grid_start(now)
+        };
+        // The time we pick frames for
+        let frame_now = match mode {
+            Mode::TargetFramesPerSecond(_) => now.advanced_by(self.frame_lead),
+            _ => now.clone(),
         };
+        let mut dropped_frames = 0;
         let frame = match mode {
+            Mode::Adaptive => unreachable!("should have been resolved by effective_mode"),
             Mode::TickOnly => None,
             Mode::OneFramePerTick => {
-                Some(tick.last_tick_before(&now, &metronome.tickrate))
+                Some(tick.last_tick_before(now, &self.tickrate))
             },
             Mode::UnlimitedFrames => Some(PreciseInstant::from(now.clone())),
             Mode::TargetFramesPerSecond(rate) => {
-                debug_assert_eq!(Some(rate), metronome.last_framerate);
-                match metronome.last_frame.as_ref() {
-                    Some(last_frame) => Some(last_frame.last_tick_before(&now, &rate)),
-                    None => Some(tick.last_tick_before(&now, &metronome.tickrate)),
+                match last_frame {
+                    // When the framerate is the tickrate, put frames right
+                    // on the ticks, even if the frame grid started out
+                    // somewhere else (say, before a `set_tickrate`).
+                    Some(last_frame) if rate == self.tickrate => {
+                        dropped_frames = last_frame.ticks_until(&frame_now, &rate).saturating_sub(1) as u64;
+                        Some(future_tick.unwrap_or(&tick).last_tick_before(&frame_now, &rate))
+                    },
+                    Some(last_frame) => {
+                        // every frame after the last, except the one we pick
+                        let surplus = last_frame.ticks_until(&frame_now, &rate);
+                        dropped_frames = surplus.saturating_sub(1) as u64;
+                        Some(last_frame.nth(surplus, &rate)
+                            .unwrap_or_else(|| PreciseInstant::from(frame_now.clone())))
+                    },
+                    None => Some(tick.last_tick_before(&frame_now, &self.tickrate)),
+                }
+            },
+            Mode::RenderOnly { target_fps } => {
+                match last_frame {
+                    Some(last_frame) => Some(last_frame.last_tick_before(now, &target_fps)),
+                    None => Some(PreciseInstant::from(now.clone())),
                 }
             },
         };
+        let candidate_frame = frame.clone();
+        let mut future_frame = false;
         let frame = frame.and_then(|frame| {
-            if frame.at > now {
+            if frame.at > frame_now {
                 // Don't render a frame in the future
+                future_frame = mode.target_framerate().is_some();
                 return None
-            } else if let Some(last_frame) = metronome.last_frame.as_ref() {
+            } else if let Some(last_frame) = last_frame {
                 // Don't render the same frame twice
                 if *last_frame == frame { return None }
             }
             Some(frame)
         });
+        // If we don't render now, the next frame is the one after the last
+        // one, or, if there was no last one, wherever we were going to put
+        // this one.
+        let next_frame = |rate: &Rate| {
+            frame.clone()
+                .or_else(|| last_frame.and_then(|x| x.next(rate)))
+                .or(candidate_frame)
+                .unwrap()
+        };
+        let until_frame = match mode {
+            Mode::OneFramePerTick => tick.at.time_since(now),
+            Mode::RenderOnly { target_fps } => next_frame(&target_fps).at.time_since(now),
+            Mode::TargetFramesPerSecond(rate) => next_frame(&rate).at.time_since(&frame_now),
+            _ => None,
+        };
         let idle_for = match mode {
             Mode::TickOnly | Mode::OneFramePerTick => {
                 // will be None or Some(ZERO) if we don't need to idle
-                tick.at.time_since(&now)
+                tick.at.time_since(now)
             },
-            Mode::TargetFramesPerSecond(rate) => {
-                let a = tick.at.time_since(&now);
-                let b = frame.as_ref().map(Clone::clone).unwrap_or_else(|| {
-                    metronome.last_frame.as_ref().unwrap().next(&rate)
-                }).at.time_since(&now);
-                match (a, b) {
+            Mode::RenderOnly { .. } => until_frame,
+            Mode::TargetFramesPerSecond(_) => {
+                let a = tick.at.time_since(now);
+                match (a, until_frame) {
                     (Some(a), Some(b)) => Some(a.min(b)),
                     _ => None,
                 }
             },
-            Mode::UnlimitedFrames => None,
+            Mode::UnlimitedFrames | Mode::Adaptive => None,
         };
+        #[cfg(not(feature="no_std"))]
+        let (extra_frames, idle_for) = {
+            let (extra_frames, extra_idle_for) = self.extra_frames(mode, &frame_now, time_went_backwards);
+            let idle_for = match (idle_for, extra_idle_for) {
+                (Some(a), Some(b)) if mode != Mode::UnlimitedFrames => Some(a.min(b)),
+                (idle_for, _) => idle_for,
+            };
+            (extra_frames, idle_for)
+        };
+        // While paused, there are no ticks to wait for
+        let idle_for = if self.paused_at.is_some() {
+            match mode {
+                Mode::TickOnly | Mode::OneFramePerTick => Some(self.tickrate.duration_per),
+                _ => until_frame,
+            }
+        } else { idle_for };
+        // An idle shorter than the usual overshoot would end too late anyway
+        let idle_for = idle_for.map(|x| x.saturating_sub(self.sleep_overshoot));
         let idle_for = match idle_for {
             None | Some(Duration::ZERO) => None,
-            x => x,
+            Some(x) => Some(match self.max_idle {
+                Some(max) => x.min(max),
+                None => x,
+            }),
         };
         let want_future = if let Some(_frame) = frame.as_ref() {
             mode.needs_a_future()
         } else { false };
-        let tick = if want_future || tick.at <= now {
+        #[cfg(not(feature="no_std"))]
+        let want_future = want_future || !extra_frames.is_empty();
+        let tick = if let Mode::RenderOnly { .. } = mode {
+            None
+        } else if self.paused_at.is_some() {
+            None
+        } else if want_future || tick.at <= *now {
             Some(tick)
         } else { None };
+        let framerate_mismatch = match mode {
+            Mode::TargetFramesPerSecond(rate) if self.strict => {
+                wildly_mismatched(&self.tickrate, &rate)
+                    && self.reported_mismatch != Some((self.tickrate, rate))
+            },
+            _ => false,
+        };
+        Plan {
+            time_went_backwards, framerate_mismatch, end_of_time: false, tick, frame, idle_for,
+            until_frame, dropped_frames, future_frame,
+            #[cfg(not(feature="no_std"))]
+            extra_frames,
+        }
+    }
+    /// Save the timing state of this `Metronome`: where the tick grid is,
+    /// the last frame, the tickrate, and the counters. Pass it to
+    /// [`restore`](#method.restore) later, e.g. after loading a saved game,
+    /// to pick up with exactly the same interpolation and pacing.
+    ///
+    /// Settings made with the `set_*` methods, and extra outputs, are not
+    /// saved. Set them up again after restoring.
+    pub fn snapshot(&self) -> MetronomeSnapshot<N::Instant> {
+        fn instant<I: TemporalSample>(x: &Option<PreciseInstant<I>>) -> Option<(I, u64)> {
+            x.as_ref().map(|x| (x.at.clone(), x.residual))
+        }
+        MetronomeSnapshot {
+            past_tick: instant(&self.past_tick),
+            future_tick: instant(&self.future_tick),
+            last_frame: instant(&self.last_frame),
+            tickrate: (self.tickrate.numerator.get(), self.tickrate.denominator.get()),
+            last_framerate: self.last_framerate.map(|x| (x.numerator.get(), x.denominator.get())),
+            max_ticks_behind: self.max_ticks_behind,
+            tick_number: self.tick_number,
+            frames_produced: self.frames_produced,
+            tickrate_changed: self.tickrate_changed,
+            first_tick_pending: self.first_tick_pending,
+        }
+    }
+    /// Create a `Metronome` from a [`snapshot`](#method.snapshot), using
+    /// `now_source`. The snapshot's instants are used as-is, so `now_source`
+    /// must be on the same timeline as the one the snapshot came from: in
+    /// practice, a `FakeNowSource` (or your own `NowSource`) picking up where
+    /// the old one left off. Restoring a snapshot of a `RealtimeNowSource`'s
+    /// instants only makes sense within the same run of the program.
+    ///
+    /// PANICS if the snapshot contains an invalid rate, which can only happen
+    /// if it was deserialized from corrupted data.
+    pub fn restore(snapshot: MetronomeSnapshot<N::Instant>, now_source: N) -> Metronome<N> {
+        fn instant<I: TemporalSample>(x: Option<(I, u64)>) -> Option<PreciseInstant<I>> {
+            x.map(|(at, residual)| PreciseInstant { at, residual })
+        }
+        let rate = |(numerator, denominator)| Rate::per_second_u64(numerator, denominator);
+        let mut metronome = Metronome::new(now_source, rate(snapshot.tickrate), snapshot.max_ticks_behind);
+        metronome.past_tick = instant(snapshot.past_tick);
+        metronome.future_tick = instant(snapshot.future_tick);
+        metronome.last_frame = instant(snapshot.last_frame);
+        metronome.last_framerate = snapshot.last_framerate.map(rate);
+        metronome.tick_number = snapshot.tick_number;
+        metronome.frames_produced = snapshot.frames_produced;
+        metronome.tickrate_changed = snapshot.tickrate_changed;
+        metronome.first_tick_pending = snapshot.first_tick_pending;
+        metronome
+    }
+    /// Make a copy of this `Metronome`, with all of its configuration and
+    /// timing state, that reads a different `NowSource`. This is handy for
+    /// replaying a configuration against a fake clock, for example.
+    ///
+    /// The new `NowSource` can be of a different type, but it must produce
+    /// the same type of instant, since the tick grid and everything else is
+    /// carried over as-is. (So, for example, you can't move from a
+    /// `RealtimeNowSource` to a `FakeNowSource`; for that, configure a fresh
+    /// `Metronome`. Use [`snapshot`](#method.snapshot) if you need to carry
+    /// timing state across instant types.) For the copy to make sense, the
+    /// new clock should also agree with the old one about what time it is.
+    pub fn clone_with_source<M: NowSource<Instant = N::Instant>>(&self, now_source: M) -> Metronome<M> {
+        Metronome {
+            label: self.label,
+            now_source,
+            past_tick: self.past_tick.clone(),
+            future_tick: self.future_tick.clone(),
+            last_frame: self.last_frame.clone(),
+            tickrate: self.tickrate,
+            last_framerate: self.last_framerate,
+            max_ticks_behind: self.max_ticks_behind,
+            tick_number: self.tick_number,
+            frames_produced: self.frames_produced,
+            dropped_frames: self.dropped_frames,
+            future_frames_skipped: self.future_frames_skipped,
+            first_now: self.first_now.clone(),
+            idle_total: self.idle_total,
+            interpolation_window: self.interpolation_window.clone(),
+            last_frame_position: self.last_frame_position,
+            frame_step_range: self.frame_step_range,
+            last_now: self.last_now.clone(),
+            frame_bracket: self.frame_bracket.clone(),
+            cadence_start: self.cadence_start.clone(),
+            cadence_last: self.cadence_last.clone(),
+            cadence_votes: self.cadence_votes,
+            adaptive_framerate: self.adaptive_framerate,
+            phase_snap: self.phase_snap,
+            phase_easing: self.phase_easing,
+            deterministic_phase: self.deterministic_phase,
+            frame_lead: self.frame_lead,
+            render_delay: self.render_delay,
+            max_idle: self.max_idle,
+            recheck_idle: self.recheck_idle,
+            backward_tolerance: self.backward_tolerance,
+            clock_stall_threshold: self.clock_stall_threshold,
+            stalled_samples: self.stalled_samples,
+            last_sampled_now: self.last_sampled_now.clone(),
+            sleep_overshoot: self.sleep_overshoot,
+            tickrate_changed: self.tickrate_changed,
+            first_tick_pending: self.first_tick_pending,
+            announce_first_tick: self.announce_first_tick,
+            slow_tick_divisor: self.slow_tick_divisor,
+            paused_at: self.paused_at.clone(),
+            paused_phase: self.paused_phase,
+            strict: self.strict,
+            first_tick_delayed: self.first_tick_delayed,
+            skip_frame_on_ticks_lost: self.skip_frame_on_ticks_lost,
+            never_empty: self.never_empty,
+            reported_mismatch: self.reported_mismatch,
+            anchor: self.anchor.clone(),
+            catch_up_policy: self.catch_up_policy,
+            catch_up_style: self.catch_up_style,
+            boundary_phase: self.boundary_phase,
+            resume_from: self.resume_from.clone(),
+            #[cfg(not(feature="no_std"))]
+            outputs: self.outputs.clone(),
+            #[cfg(not(feature="no_std"))]
+            history: self.history.clone(),
+            #[cfg(not(feature="no_std"))]
+            history_depth: self.history_depth,
+            #[cfg(not(feature="no_std"))]
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+/// The timing state of a [`Metronome`](struct.Metronome.html), saved by
+/// [`Metronome::snapshot`](struct.Metronome.html#method.snapshot) and
+/// restored by [`Metronome::restore`](struct.Metronome.html#method.restore).
+///
+/// With the `serde` feature, this can be serialized and deserialized, as long
+/// as `I` can be. In practice, that means `I` is `Duration`, as used by
+/// [`FakeNowSource`](struct.FakeNowSource.html); a `std::time::Instant` has
+/// no meaningful serialized form.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetronomeSnapshot<I> {
+    /// Instants, with their residuals.
+    past_tick: Option<(I, u64)>,
+    future_tick: Option<(I, u64)>,
+    last_frame: Option<(I, u64)>,
+    /// Rates, as (numerator, denominator).
+    tickrate: (u64, u64),
+    last_framerate: Option<(u64, u64)>,
+    max_ticks_behind: u32,
+    tick_number: u64,
+    frames_produced: u64,
+    tickrate_changed: bool,
+    first_tick_pending: bool,
+}
+
+/// Frames for outputs added with `add_output`, by `output_id`.
+#[cfg(not(feature="no_std"))]
+type ExtraFrames<I> = Vec<(usize, PreciseInstant<I>)>;
+
+/// How many ticks after, or before, the current tick another tick is.
+enum TickOffset {
+    Forward(u32),
+    Back(u32),
+}
+
+/// An instant to interpolate at, and the ticks before and after it.
+type Bracket<I> = (PreciseInstant<I>, PreciseInstant<I>, PreciseInstant<I>);
+
+/// The decisions `sample` makes up front.
+struct Plan<I: TemporalSample> {
+    time_went_backwards: bool,
+    framerate_mismatch: bool,
+    end_of_time: bool,
+    tick: Option<PreciseInstant<I>>,
+    frame: Option<PreciseInstant<I>>,
+    idle_for: Option<Duration>,
+    /// How long until the frame after `frame`, ignoring ticks.
+    until_frame: Option<Duration>,
+    /// How many frames we're skipping to get to `frame`.
+    dropped_frames: u64,
+    /// True if a target framerate's frame was held back for being in the
+    /// future.
+    future_frame: bool,
+    /// Frames for outputs added with `add_output`, in order.
+    #[cfg(not(feature="no_std"))]
+    extra_frames: ExtraFrames<I>,
+}
+
+/// Returned by [`Metronome::sample`](struct.Metronome.html#method.sample). See
+/// that method's documentation.
+pub struct MetronomeIterator<'a, N: NowSource> {
+    metronome: &'a mut Metronome<N>,
+    now: N::Instant,
+    mode: Mode,
+    tick: Option<PreciseInstant<N::Instant>>,
+    frame: Option<PreciseInstant<N::Instant>>,
+    idle_for: Option<Duration>,
+    time_went_backwards: bool,
+    clock_stalled: bool,
+    /// Set by a tick that should be followed by a `SlowTick`.
+    slow_tick: bool,
+    framerate_mismatch: bool,
+    end_of_time: bool,
+    ticks_given: u32,
+    /// True until this batch has produced a reading.
+    empty: bool,
+    /// True once this batch has produced its main `Frame`.
+    frame_given: bool,
+    /// A reading that's already been worked out, to be returned next.
+    held: Option<Reading>,
+    /// What `next` returned when `peek` called it.
+    peeked: Option<Option<Reading>>,
+    /// Set by `sample_and_idle`: sleep on the `NowSource` before yielding an
+    /// `Idle`.
+    sleep_on_idle: bool,
+    precise_phase: Option<f64>,
+    fixed_phase: Option<u32>,
+    /// Frames skipped to get to `frame`, to be counted when it's produced.
+    dropped_frames: u64,
+    #[cfg(not(feature="no_std"))]
+    extra_frames: ExtraFrames<N::Instant>,
+    #[cfg(not(feature="no_std"))]
+    extra_frames_given: usize,
+}
+
+impl<'a, N: NowSource> MetronomeIterator<'a, N> {
+    fn new(metronome: &'a mut Metronome<N>, mode: Mode, now: N::Instant) -> MetronomeIterator<'a, N> {
+        let Plan {
+            time_went_backwards, framerate_mismatch, end_of_time, tick, frame, idle_for,
+            until_frame: _, dropped_frames, future_frame,
+            #[cfg(not(feature="no_std"))]
+            extra_frames,
+        } = metronome.plan(mode, &now);
+        if future_frame {
+            metronome.future_frames_skipped += 1;
+        }
+        if time_went_backwards {
+            metronome.past_tick = None;
+            metronome.future_tick = None;
+            metronome.forget_frames();
+            metronome.first_tick_pending = true;
+        }
+        if framerate_mismatch {
+            metronome.reported_mismatch = metronome.last_framerate
+                .map(|framerate| (metronome.tickrate, framerate));
+        }
         MetronomeIterator {
             idle_for,
             frame,
@@ -246,82 +2234,709 @@ impl<N: NowSource> MetronomeIterator<'_, N> {
             metronome,
             now,
             time_went_backwards,
+            clock_stalled: false,
+            slow_tick: false,
+            framerate_mismatch,
+            end_of_time,
             mode,
             ticks_given: 0,
+            empty: true,
+            frame_given: false,
+            held: None,
+            peeked: None,
+            sleep_on_idle: false,
+            precise_phase: None,
+            fixed_phase: None,
+            dropped_frames,
+            #[cfg(not(feature="no_std"))]
+            extra_frames,
+            #[cfg(not(feature="no_std"))]
+            extra_frames_given: 0,
         }
     }
+    /// True if a tick that isn't due yet should be delivered anyway, so that
+    /// a frame has a future tick to interpolate towards. In
+    /// `UnlimitedFrames`, frames never lead `now`, so one such tick is all
+    /// they will ever need; never deliver a second.
+    fn may_tick_early(&self) -> bool {
+        if self.frame.is_none() && !self.extra_frames_pending() { return false }
+        self.mode != Mode::UnlimitedFrames
+            || !self.metronome.future_tick.as_ref().is_some_and(|x| x.at > self.now)
+    }
+    /// True if there are frames for extra outputs left to render.
+    fn extra_frames_pending(&self) -> bool {
+        #[cfg(not(feature="no_std"))]
+        { self.extra_frames_given < self.extra_frames.len() }
+        #[cfg(feature="no_std")]
+        { false }
+    }
+    /// Render the next frame for an extra output, if the ticks are ready for
+    /// it, and if it doesn't come after the main frame (when that's ready).
+    #[cfg(not(feature="no_std"))]
+    fn next_extra_frame(&mut self, main_frame_ready: bool) -> Option<Reading> {
+        let (output_id, frame) = self.extra_frames.get(self.extra_frames_given)?;
+        let past_tick = self.metronome.past_tick.as_ref()?;
+        let future_tick = self.metronome.future_tick.as_ref()?;
+        if !self.metronome.frame_precedes(frame, future_tick) { return None }
+        if main_frame_ready && self.frame.as_ref().is_some_and(|main_frame| main_frame <= frame) {
+            return None
+        }
+        let rate = &self.metronome.outputs[output_id - 1].0;
+        let interpolating = past_tick != future_tick;
+        let phase = if interpolating {
+            frames_per_schedule(&self.metronome.tickrate, rate)
+                .and_then(|frames| scheduled_phase(&frame.at, &past_tick.at, &future_tick.at, frames))
+                .unwrap_or_else(|| phase_between(&frame.at, &past_tick.at, &future_tick.at, self.metronome.deterministic_phase))
+        } else { 1.0 };
+        let reading = Reading::Frame {
+            phase: self.metronome.ease_phase(snap_phase(phase, self.metronome.phase_snap)),
+            interpolating,
+            on_tick_boundary: frame.at == future_tick.at,
+            output_id: *output_id,
+        };
+        self.metronome.outputs[output_id - 1].1 = Some(frame.clone());
+        self.metronome.frames_produced += 1;
+        self.extra_frames_given += 1;
+        Some(reading)
+    }
+    #[cfg(feature="no_std")]
+    fn next_extra_frame(&mut self, _main_frame_ready: bool) -> Option<Reading> {
+        None
+    }
+    /// Returns `true` if this batch still has a `Frame` reading to yield.
+    /// Checked before iterating, this tells you whether the batch will render
+    /// at all, which is handy for deciding whether to acquire a swapchain
+    /// image (or similar) before chewing through the ticks.
+    pub fn will_produce_frame(&self) -> bool {
+        let is_main_frame = |x: &Option<Reading>| matches!(x, Some(Reading::Frame { output_id: 0, .. }));
+        self.frame.is_some() || is_main_frame(&self.held) || self.peeked.as_ref().is_some_and(is_main_frame)
+    }
+    /// Returns the reading that `next` will return, without consuming it.
+    /// Calling this more than once in a row returns the same reading, and
+    /// only works it out once, so nothing about the batch (or the
+    /// `Metronome`) moves on until you call `next`.
+    ///
+    /// Peeking at a `Frame` works out its phase, so
+    /// [`precise_phase`](#method.precise_phase) and
+    /// [`fixed_phase`](#method.fixed_phase) are updated as soon as you peek.
+    pub fn peek(&mut self) -> Option<&Reading> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.next());
+        }
+        self.peeked.as_ref().and_then(|x| x.as_ref())
+    }
+    /// Returns the phase of the last `Frame` this batch produced, as an `f64`
+    /// that takes the sub-nanosecond part of each tick's timing into account.
+    /// The regular `phase` is only computed to the nearest nanosecond, which
+    /// is plenty unless ticks are very short, or a very slow time scale is
+    /// making nanoseconds visible. Returns `None` if this batch hasn't
+    /// produced a `Frame` yet.
+    ///
+    /// To call this in the middle of a batch, loop with `while let` instead
+    /// of `for`:
+    ///
+    /// ```rust
+    /// # use ftvf::*;
+    /// # let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(30, 1), 5);
+    /// let mut readings = metronome.sample(Mode::UnlimitedFrames);
+    /// while let Some(reading) = readings.next() {
+    ///     match reading {
+    ///         Reading::Frame { .. } => {
+    ///             let phase = readings.precise_phase().unwrap();
+    ///             // ... render at `phase` ...
+    /// #           assert_eq!(phase, 1.0);
+    ///         },
+    ///         // ...
+    ///         _ => (),
+    ///     }
+    /// }
+    /// ```
+    pub fn precise_phase(&self) -> Option<f64> {
+        self.precise_phase
+    }
+    /// Returns the phase of the last `Frame` this batch produced as a 16.16
+    /// fixed-point number, i.e. in 65536ths, so that 65536 is a phase of 1.
+    /// It is computed entirely with integers, for renderers (and FPU-less
+    /// targets) that would rather not deal with floats at all. Like the
+    /// regular `phase`, it's computed to the nearest nanosecond, but it
+    /// rounds down, and it ignores [`set_phase_snap`][1]. Returns `None` if
+    /// this batch hasn't produced a `Frame` yet. Call it the same way as
+    /// [`precise_phase`](#method.precise_phase).
+    ///
+    /// [1]: struct.Metronome.html#method.set_phase_snap
+    pub fn fixed_phase(&self) -> Option<u32> {
+        self.fixed_phase
+    }
+    /// Turn each run of consecutive `Tick` readings into a single
+    /// [`Reading::Ticks`](enum.Reading.html#variant.Ticks), for systems that
+    /// can process a whole batch of ticks in one pass. Everything else comes
+    /// through unchanged, in the same order. `max_ticks_behind` applies to
+    /// the individual ticks as usual, so the ticks delivered before a
+    /// `TicksLost` are counted and come out just before it.
+    pub fn coalesce_ticks(self) -> CoalescedTicks<'a, N> {
+        CoalescedTicks { inner: self, held: None }
+    }
+}
+
+/// Returned by [`MetronomeIterator::coalesce_ticks`](struct.MetronomeIterator.html#method.coalesce_ticks).
+/// See that method's documentation.
+pub struct CoalescedTicks<'a, N: NowSource> {
+    inner: MetronomeIterator<'a, N>,
+    /// A reading we took from `inner` while counting ticks, and will return
+    /// next.
+    held: Option<Reading>,
+}
+
+impl<N: NowSource> Iterator for CoalescedTicks<'_, N> {
+    type Item = Reading;
+    fn next(&mut self) -> Option<Reading> {
+        if let Some(held) = self.held.take() {
+            return Some(held)
+        }
+        let mut count = 0;
+        for reading in self.inner.by_ref() {
+            match reading {
+                Reading::Tick { .. } => count += 1,
+                _ if count == 0 => return Some(reading),
+                _ => {
+                    self.held = Some(reading);
+                    break
+                },
+            }
+        }
+        if count == 0 { None }
+        else { Some(Reading::Ticks { count }) }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.inner.size_hint();
+        (0, upper.map(|x| x + self.held.is_some() as usize))
+    }
+}
+
+/// Which of the two `Metronome`s passed to [`merge_ticks`](fn.merge_ticks.html)
+/// a reading came from.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Which {
+    /// The first `Metronome`, `a`.
+    A,
+    /// The second `Metronome`, `b`.
+    B,
+}
+
+/// Sample two `Metronome`s at once, in the same `mode`, and deliver their
+/// readings in a single stream, with the ticks in chronological order. This
+/// is for running two systems at different tickrates (say, gameplay and an
+/// audio DSP) on the same thread, where a tick of one has to be processed
+/// after the ticks of the other that came before it.
+///
+/// Each `Metronome`'s own readings come out in the same order they would on
+/// their own. Ticks that happen at the same instant come from `a` first.
+/// Anything else (frames, `Idle`, etc.) comes out as soon as it's reached,
+/// so you may get an `Idle` from each. The two `Metronome`s can read the
+/// same `NowSource` or different ones of the same type, as long as their
+/// instants are comparable.
+pub fn merge_ticks<'a, N: NowSource>(a: &'a mut Metronome<N>, b: &'a mut Metronome<N>, mode: Mode) -> MergedTicks<'a, N> {
+    MergedTicks {
+        a: a.sample(mode),
+        b: b.sample(mode),
+        held_a: None,
+        held_b: None,
+    }
+}
+
+/// Returned by [`merge_ticks`](fn.merge_ticks.html). See that function's
+/// documentation.
+pub struct MergedTicks<'a, N: NowSource> {
+    a: MetronomeIterator<'a, N>,
+    b: MetronomeIterator<'a, N>,
+    /// The next reading from each side, and its instant if it's a tick.
+    held_a: Option<(Reading, Option<N::Instant>)>,
+    held_b: Option<(Reading, Option<N::Instant>)>,
+}
+
+impl<N: NowSource> MergedTicks<'_, N> {
+    fn fill(readings: &mut MetronomeIterator<'_, N>, held: &mut Option<(Reading, Option<N::Instant>)>) {
+        if held.is_some() { return }
+        *held = readings.next().map(|reading| {
+            let instant = match reading {
+                Reading::Tick { .. } => readings.metronome.future_tick.as_ref().map(|x| x.at.clone()),
+                _ => None,
+            };
+            (reading, instant)
+        });
+    }
+}
+
+impl<N: NowSource> Iterator for MergedTicks<'_, N> {
+    type Item = (Which, Reading);
+    fn next(&mut self) -> Option<(Which, Reading)> {
+        Self::fill(&mut self.a, &mut self.held_a);
+        Self::fill(&mut self.b, &mut self.held_b);
+        let which = match (self.held_a.as_ref(), self.held_b.as_ref()) {
+            (None, None) => return None,
+            (Some(_), None) | (Some((_, None)), _) => Which::A,
+            (None, Some(_)) | (_, Some((_, None))) => Which::B,
+            (Some((_, Some(a))), Some((_, Some(b)))) => if b < a { Which::B } else { Which::A },
+        };
+        let held = match which {
+            Which::A => self.held_a.take(),
+            Which::B => self.held_b.take(),
+        };
+        held.map(|(reading, _)| (which, reading))
+    }
 }
 
 impl<N: NowSource> Iterator for MetronomeIterator<'_, N> {
     type Item = Reading;
     fn next(&mut self) -> Option<Reading> {
+        if let Some(peeked) = self.peeked.take() {
+            return peeked
+        }
+        let reading = match self.held.take() {
+            Some(held) => Some(held),
+            None => self.next_reading(),
+        };
+        let reading = match reading {
+            Some(Reading::Tick { mut dt }) if self.metronome.catch_up_style == CatchUpStyle::SingleBigStep => {
+                loop {
+                    match self.next_reading() {
+                        Some(Reading::Tick { dt: more }) => dt += more,
+                        other => {
+                            self.held = other;
+                            break
+                        },
+                    }
+                }
+                Some(Reading::Tick { dt })
+            },
+            reading => reading,
+        };
+        if reading.is_some() {
+            self.empty = false;
+        } else if self.empty && self.metronome.never_empty {
+            self.empty = false;
+            return Some(Reading::NoOp)
+        }
+        if let (true, Some(Reading::Idle { duration })) = (self.sleep_on_idle, reading.as_ref()) {
+            self.metronome.now_source.sleep(*duration);
+        }
+        reading
+    }
+    /// The upper bound is the most readings this batch could still produce:
+    /// up to `max_ticks_behind` ticks, then a `TicksLost`, a frame, an idle,
+    /// and any anomalies. The lower bound is always zero.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ticks = if self.tick.is_some() {
+            self.metronome.max_ticks_behind.saturating_sub(self.ticks_given) as usize + 1
+                + self.metronome.tickrate_changed as usize
+                + (self.metronome.first_tick_pending && self.metronome.announce_first_tick) as usize
+        } else { 0 };
+        let ticks = match self.metronome.slow_tick_divisor {
+            0 => ticks,
+            divisor => ticks + ticks.div_ceil(divisor as usize),
+        };
+        let others = [
+            self.slow_tick,
+            self.time_went_backwards,
+            self.clock_stalled,
+            self.framerate_mismatch,
+            self.end_of_time,
+            self.frame.is_some(),
+            self.idle_for.is_some(),
+            self.held.is_some(),
+            matches!(self.peeked, Some(Some(_))),
+            self.empty && self.metronome.never_empty,
+        ].into_iter().filter(|x| *x).count();
+        #[cfg(not(feature="no_std"))]
+        let others = others + self.extra_frames.len() - self.extra_frames_given;
+        (0, Some(ticks + others))
+    }
+}
+
+impl<N: NowSource> MetronomeIterator<'_, N> {
+    fn next_reading(&mut self) -> Option<Reading> {
+        if self.slow_tick {
+            self.slow_tick = false;
+            return Some(Reading::SlowTick)
+        }
         if self.time_went_backwards {
             self.time_went_backwards = false;
             return Some(Reading::TimeWentBackwards)
         }
+        if self.clock_stalled {
+            self.clock_stalled = false;
+            return Some(Reading::ClockStalled)
+        }
+        if self.framerate_mismatch {
+            self.framerate_mismatch = false;
+            return Some(Reading::FramerateMismatch)
+        }
+        if self.end_of_time {
+            self.end_of_time = false;
+            return Some(Reading::EndOfTime)
+        }
         let should_render_now = match (self.tick.as_ref(), self.frame.as_ref()) {
             (Some(_tick), Some(frame)) => {
                 match (self.metronome.past_tick.as_ref(), self.metronome.future_tick.as_ref()) {
-                    (Some(past), Some(future)) => frame >= past && frame <= future,
+                    (Some(past), Some(future)) => frame >= past
+                        && self.metronome.frame_precedes(frame, future),
                     _ => false,
                 }
             },
             (None, Some(_)) => true,
             _ => false,
         };
+        if let Some(reading) = self.next_extra_frame(should_render_now) {
+            return Some(reading)
+        }
         if !should_render_now {
             if let Some(tick) = self.tick.take() {
-                if tick.at <= self.now || self.frame.is_some() {
+                if tick.at <= self.now || self.may_tick_early() {
                     if self.ticks_given >= self.metronome.max_ticks_behind {
                         // Enough ticks have been delivered. Complain.
+                        // The restarted grid will stand in for the last of
+                        // the lost ticks.
+                        let lost = tick.ticks_until(&self.now, &self.metronome.tickrate);
+                        self.metronome.tick_number += lost as u64;
+                        if self.metronome.catch_up_policy == CatchUpPolicy::StayAnchored {
+                            self.metronome.resume_from = Some(tick.last_tick_before(&self.now, &self.metronome.tickrate));
+                        }
                         self.metronome.past_tick = None;
                         self.metronome.future_tick = None;
-                        self.metronome.last_frame = None;
+                        self.metronome.forget_frames();
                         // self.tick has already been None'd
                         // self.frame may (or may not) lead to us eventually
-                        // rendering
+                        // rendering, unless we were asked not to
+                        if self.metronome.skip_frame_on_ticks_lost {
+                            self.frame = None;
+                            #[cfg(not(feature="no_std"))]
+                            { self.extra_frames_given = self.extra_frames.len(); }
+                        }
                         return Some(Reading::TicksLost)
                     }
+                    if self.metronome.tickrate_changed {
+                        self.metronome.tickrate_changed = false;
+                        self.tick = Some(tick);
+                        return Some(Reading::TickrateChanged { new: self.metronome.tickrate })
+                    }
+                    if self.metronome.first_tick_pending {
+                        self.metronome.first_tick_pending = false;
+                        if self.metronome.announce_first_tick {
+                            self.tick = Some(tick);
+                            return Some(Reading::FirstTick)
+                        }
+                    }
                     self.metronome.past_tick = self.metronome.future_tick.take();
                     self.metronome.future_tick = Some(tick.clone());
+                    self.metronome.resume_from = None;
+                    self.metronome.remember_tick(&tick.at);
                     if self.metronome.past_tick.is_none() {
                         self.metronome.past_tick = self.metronome.future_tick.clone();
                     }
-                    let tick = tick.next(&self.metronome.tickrate);
-                    self.tick = Some(tick);
-                    return Some(Reading::Tick);
+                    #[cfg(feature="debug_checks")]
+                    if self.mode == Mode::UnlimitedFrames {
+                        debug_assert!(self.metronome.past_tick.as_ref().is_none_or(|x| x.at <= self.now),
+                                      "delivered a second tick ahead of now");
+                    }
+                    let next_tick = tick.next(&self.metronome.tickrate);
+                    let dt = match next_tick.as_ref() {
+                        Some(next_tick) => next_tick.at.time_since(&tick.at).unwrap_or(Duration::ZERO),
+                        None => {
+                            // This is the last tick there will ever be.
+                            self.end_of_time = true;
+                            self.metronome.tickrate.duration_per
+                        },
+                    };
+                    self.tick = next_tick;
+                    self.ticks_given += 1;
+                    self.metronome.tick_number += 1;
+                    let divisor = self.metronome.slow_tick_divisor as u64;
+                    self.slow_tick = divisor != 0 && self.metronome.tick_number.is_multiple_of(divisor);
+                    return Some(Reading::Tick { dt });
                 }
             }
         }
         // We got here because we didn't tick. Maybe we didn't tick because we
         // need to render.
         if let Some(frame) = self.frame.take() {
-            let phase = match self.mode {
-                Mode::TickOnly => unreachable!(),
-                Mode::OneFramePerTick => 1.0,
+            debug_assert!(!self.frame_given, "a batch produced more than one frame");
+            self.frame_given = true;
+            // the denominator of `frame.residual`
+            let frame_denominator = match self.mode {
+                Mode::TargetFramesPerSecond(rate) | Mode::RenderOnly { target_fps: rate }
+                    => rate.numerator.get(),
+                _ => 1,
+            };
+            // The instant to interpolate at, and the ticks on either side
+            let bracket = match self.mode {
+                Mode::UnlimitedFrames | Mode::TargetFramesPerSecond(_) => self.metronome.delayed_bracket(&frame),
+                _ => None,
+            };
+            let interpolating = match self.mode {
+                Mode::UnlimitedFrames | Mode::TargetFramesPerSecond(_) => bracket.is_some(),
+                Mode::RenderOnly { .. } => self.metronome.interpolation_window.as_ref()
+                    .is_some_and(|(prev, next)| prev < next),
+                _ => false,
+            };
+            let (phase, precise_phase, fixed_phase) = match self.mode {
+                Mode::TickOnly | Mode::Adaptive => unreachable!(),
+                Mode::OneFramePerTick => (1.0, 1.0, FIXED_ONE as u32),
                 Mode::UnlimitedFrames | Mode::TargetFramesPerSecond(_) => {
-                    match (self.metronome.past_tick.as_ref(), self.metronome.future_tick.as_ref()) {
-                        (Some(past_tick), Some(future_tick)) if past_tick != future_tick => {
-                            if frame.at < past_tick.at { 0.0 }
-                            else if frame.at > future_tick.at { 1.0 }
-                            else {
-                                let tick_step = future_tick.at.time_since(&past_tick.at).unwrap();
-                                let frame_offset = frame.at.time_since(&past_tick.at).unwrap();
-                                frame_offset.as_nanos() as f32 / tick_step.as_nanos() as f32
-                            }
+                    let schedule = match self.mode {
+                        Mode::TargetFramesPerSecond(rate) => frames_per_schedule(&self.metronome.tickrate, &rate),
+                        _ => None,
+                    };
+                    match bracket.as_ref() {
+                        Some((at, past_tick, future_tick)) => {
+                            (schedule.and_then(|frames| scheduled_phase(&at.at, &past_tick.at, &future_tick.at, frames))
+                                .unwrap_or_else(|| phase_between(&at.at, &past_tick.at, &future_tick.at, self.metronome.deterministic_phase)),
+                             precise_phase_between(at, frame_denominator, past_tick, future_tick, self.metronome.tickrate.numerator.get()),
+                             fixed_phase_between(&at.at, &past_tick.at, &future_tick.at))
                         },
-                        _ => 1.0,
+                        None => (1.0, 1.0, FIXED_ONE as u32),
                     }
                 },
+                Mode::RenderOnly { .. } => {
+                    match self.metronome.interpolation_window.as_ref() {
+                        Some((prev, next)) if prev < next => {
+                            (phase_between(&frame.at, prev, next, self.metronome.deterministic_phase),
+                             precise_phase_between(&frame, frame_denominator, &PreciseInstant::from(prev.clone()), &PreciseInstant::from(next.clone()), 1),
+                             fixed_phase_between(&frame.at, prev, next))
+                        },
+                        _ => (1.0, 1.0, FIXED_ONE as u32),
+                    }
+                },
+            };
+            let (phase, precise_phase, fixed_phase) = match self.metronome.paused_phase {
+                Some(paused) => (paused, paused as f64, (paused as f64 * FIXED_ONE as f64) as u32),
+                None => (phase, precise_phase, fixed_phase),
+            };
+            self.fixed_phase = Some(fixed_phase);
+            let current_tick = match self.mode {
+                Mode::RenderOnly { .. } => self.metronome.interpolation_window.as_ref()
+                    .map(|(_, next)| next),
+                _ => self.metronome.future_tick.as_ref().map(|x| &x.at),
             };
+            let on_tick_boundary = match bracket.as_ref() {
+                Some((at, past_tick, future_tick)) => match self.metronome.boundary_phase {
+                    BoundaryPhase::EndOfInterval => *at == *future_tick,
+                    BoundaryPhase::StartOfInterval => *at == *past_tick,
+                },
+                None => current_tick
+                    .map(|current_tick| *current_tick == frame.at)
+                    .unwrap_or(false),
+            };
+            let phase = snap_phase(phase, self.metronome.phase_snap);
+            let snap = self.metronome.phase_snap as f64;
+            self.precise_phase = Some(if precise_phase < snap { 0.0 }
+            else if precise_phase > 1.0 - snap { 1.0 }
+            else { precise_phase });
+            let tick_number = self.metronome.tick_number;
+            if let Some((last_tick_number, last_phase)) = self.metronome.last_frame_position {
+                let step = (tick_number - last_tick_number) as f32 + phase - last_phase;
+                self.metronome.frame_step_range = Some(match self.metronome.frame_step_range {
+                    Some((min, max)) => (min.min(step), max.max(step)),
+                    None => (step, step),
+                });
+            }
+            let frame_bracket = match self.mode {
+                Mode::RenderOnly { .. } => self.metronome.interpolation_window.clone()
+                    .filter(|(prev, next)| prev < next),
+                Mode::UnlimitedFrames | Mode::TargetFramesPerSecond(_) => bracket
+                    .map(|(_, past_tick, future_tick)| (past_tick.at, future_tick.at)),
+                _ => match (self.metronome.past_tick.as_ref(), self.metronome.future_tick.as_ref()) {
+                    (Some(past_tick), Some(future_tick)) if past_tick != future_tick
+                        => Some((past_tick.at.clone(), future_tick.at.clone())),
+                    _ => None,
+                },
+            };
+            #[cfg(feature="debug_checks")]
+            if let (Some((_, last_phase)), Some(_)) = (self.metronome.last_frame_position, frame_bracket.as_ref()) {
+                if self.metronome.frame_bracket == frame_bracket {
+                    debug_assert!(phase >= last_phase, "frame phase went from {} back to {} between the same two ticks (is your NowSource going backwards?)", last_phase, phase);
+                }
+            }
+            self.metronome.last_frame_position = Some((tick_number, phase));
+            self.metronome.frame_bracket = frame_bracket;
+            self.metronome.remember_frame(&frame.at);
             self.metronome.last_frame = Some(frame);
-            // if we render, do not tick again
-            self.tick = None;
-            return Some(Reading::Frame { phase });
+            self.metronome.frames_produced += 1;
+            self.metronome.dropped_frames += self.dropped_frames;
+            // if we render, do not tick again (unless another output still
+            // needs us to)
+            if !self.extra_frames_pending() {
+                self.tick = None;
+            }
+            let phase = self.metronome.ease_phase(phase);
+            return Some(Reading::Frame { phase, interpolating, on_tick_boundary, output_id: 0 });
         }
         if let Some(duration) = self.idle_for.take() {
+            let (start, duration) = if self.metronome.recheck_idle {
+                let deadline = self.now.advanced_by(duration);
+                let now = self.metronome.read_clock();
+                self.metronome.last_now = Some(now.clone());
+                match deadline.time_since(&now) {
+                    Some(duration) if !duration.is_zero() => (now, duration),
+                    _ => return None,
+                }
+            } else { (self.now.clone(), duration) };
+            self.metronome.remember_idle(&start, duration);
             return Some(Reading::Idle { duration });
         }
         None
     }
 }
+
+/// As `phase_between`, but taking residuals into account. Each residual is
+/// over the given denominator.
+fn precise_phase_between<I: TemporalSample>(at: &PreciseInstant<I>, at_denominator: u64, past: &PreciseInstant<I>, future: &PreciseInstant<I>, tick_denominator: u64) -> f64 {
+    fn nanos_since<I: TemporalSample>(a: &PreciseInstant<I>, a_denominator: u64, b: &PreciseInstant<I>, b_denominator: u64) -> f64 {
+        let whole = match a.at.time_since(&b.at) {
+            Some(x) => x.as_nanos() as f64,
+            None => -(b.at.time_since(&a.at).unwrap().as_nanos() as f64),
+        };
+        whole + a.residual as f64 / a_denominator as f64
+            - b.residual as f64 / b_denominator as f64
+    }
+    let tick_step = nanos_since(future, tick_denominator, past, tick_denominator);
+    let frame_offset = nanos_since(at, at_denominator, past, tick_denominator);
+    if tick_step <= 0.0 { 1.0 }
+    else { (frame_offset / tick_step).clamp(0.0, 1.0) }
+}
+
+/// True if `framerate` is less than a hundredth of `tickrate`, or more than a
+/// thousand times it.
+fn wildly_mismatched(tickrate: &Rate, framerate: &Rate) -> bool {
+    // Cross-multiply to compare the fractions exactly.
+    let tick = tickrate.numerator.get() as u128 * framerate.denominator.get() as u128;
+    let frame = framerate.numerator.get() as u128 * tickrate.denominator.get() as u128;
+    frame * 100 < tick || frame > tick * 1000
+}
+
+/// Where `at` lies between `past` and `future`, as a fraction (numerator,
+/// denominator) of nanoseconds, clamped to between 0 and 1.
+fn offset_and_step<I: TemporalSample>(at: &I, past: &I, future: &I) -> (u128, u128) {
+    if at < past { (0, 1) }
+    else if at > future { (1, 1) }
+    else {
+        let tick_step = future.time_since(past).unwrap();
+        let frame_offset = at.time_since(past).unwrap();
+        (frame_offset.as_nanos(), tick_step.as_nanos())
+    }
+}
+
+/// Snap `phase` to 0 or 1 if it's within `snap` of either.
+fn snap_phase(phase: f32, snap: f32) -> f32 {
+    if phase < snap { 0.0 }
+    else if phase > 1.0 - snap { 1.0 }
+    else { phase }
+}
+
+/// Where `at` lies between `past` and `future`, from 0 to 1, clamped.
+fn phase_between<I: TemporalSample>(at: &I, past: &I, future: &I, deterministic: bool) -> f32 {
+    let (frame_offset, tick_step) = offset_and_step(at, past, future);
+    if deterministic {
+        deterministic_ratio(frame_offset, tick_step)
+    } else {
+        frame_offset as f32 / tick_step as f32
+    }
+}
+
+/// If the pattern of frames at `framerate` against ticks at `tickrate`
+/// repeats every N frames, for small enough N, returns N. In that case, every
+/// frame's phase is a multiple of 1/N, and ticks are long enough compared to
+/// N that nanosecond rounding can't hide which multiple.
+fn frames_per_schedule(tickrate: &Rate, framerate: &Rate) -> Option<u64> {
+    const MAX_FRAMES: u64 = 65536;
+    // ticks per frame = ticks / frames
+    let ticks = tickrate.numerator.get().checked_mul(framerate.denominator.get())?;
+    let frames = framerate.numerator.get().checked_mul(tickrate.denominator.get())?;
+    let frames = frames / gcd(ticks, frames);
+    if frames <= MAX_FRAMES && tickrate.duration_per.as_nanos() >= frames as u128 * 4 {
+        Some(frames)
+    } else { None }
+}
+
+/// Where `at` lies between `past` and `future`, as an exact multiple of
+/// `1 / frames`. `None` if it's not close to one, which means the frame grid
+/// isn't lined up with the tick grid (e.g. because the tickrate changed).
+fn scheduled_phase<I: TemporalSample>(at: &I, past: &I, future: &I, frames: u64) -> Option<f32> {
+    let (frame_offset, tick_step) = offset_and_step(at, past, future);
+    let scaled = frame_offset * frames as u128;
+    let multiple = (scaled + tick_step / 2) / tick_step;
+    // A real member of the pattern is well within a hundredth of a step of
+    // its multiple.
+    if scaled.abs_diff(multiple * tick_step) * 100 > tick_step { return None }
+    Some(multiple as f32 / frames as f32)
+}
+
+/// A phase of 1, in the units of `fixed_phase`.
+const FIXED_ONE: u128 = 1 << 16;
+
+/// Where `at` lies between `past` and `future`, from 0 to `FIXED_ONE`,
+/// clamped and rounded down.
+fn fixed_phase_between<I: TemporalSample>(at: &I, past: &I, future: &I) -> u32 {
+    let (frame_offset, tick_step) = offset_and_step(at, past, future);
+    (frame_offset * FIXED_ONE / tick_step) as u32
+}
+
+/// `offset / step`, for `offset <= step`, rounded to the nearest 2^-24. The
+/// rounded numerator fits in an `f32` mantissa, and dividing by a power of two
+/// is exact, so no floating point rounding ever happens.
+fn deterministic_ratio(offset: u128, step: u128) -> f32 {
+    const ONE: u128 = 1 << 24;
+    let numerator = (offset * ONE + step / 2) / step;
+    numerator as f32 / ONE as f32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FakeNowSource;
+    use core::cell::RefCell;
+    #[test]
+    fn mode_helpers() {
+        let rate = Rate::per_second(50, 1);
+        for (mode, headless, framerate) in [
+            (Mode::TickOnly, true, None),
+            (Mode::OneFramePerTick, false, None),
+            (Mode::UnlimitedFrames, false, None),
+            (Mode::TargetFramesPerSecond(rate), false, Some(rate)),
+            (Mode::RenderOnly { target_fps: rate }, false, Some(rate)),
+            (Mode::Adaptive, false, None),
+        ] {
+            assert_eq!(mode.is_headless(), headless, "{:?}", mode);
+            assert_eq!(mode.target_framerate(), framerate, "{:?}", mode);
+        }
+    }
+    #[test]
+    #[cfg(not(feature="no_std"))]
+    fn default() {
+        let metronome = Metronome::default();
+        assert_eq!(metronome.tickrate, Rate::per_second(60, 1));
+        assert_eq!(metronome.max_ticks_behind, 3);
+        assert!(!metronome.has_started());
+    }
+    #[test]
+    fn run_ticks_matches_realtime() {
+        let rate = Rate::per_second(60000, 1001);
+        let mut fast = Metronome::new(FakeNowSource::default(), rate, 5);
+        let mut ticks = 0;
+        fast.run_ticks(1000, || ticks += 1);
+        assert_eq!(ticks, 1000);
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut slow = Metronome::new(&now_source, rate, 1000);
+        assert_eq!(slow.sample(Mode::TickOnly).filter(|x| matches!(x, Reading::Tick{..})).count(), 1);
+        // The thousandth tick (tick 999) happens at exactly 999*1001/60000
+        // seconds.
+        now_source.borrow_mut().now = Duration::from_nanos(16_666_650_000);
+        assert_eq!(slow.sample(Mode::TickOnly).filter(|x| matches!(x, Reading::Tick{..})).count(), 999);
+        assert_eq!(fast.tick_number, slow.tick_number);
+        for (a, b) in [(&fast.past_tick, &slow.past_tick), (&fast.future_tick, &slow.future_tick)] {
+            let (a, b) = (a.as_ref().unwrap(), b.as_ref().unwrap());
+            assert_eq!(a.at, b.at);
+            assert_eq!(a.residual, b.residual);
+        }
+        assert_eq!(fast.future_tick.unwrap().at, Duration::from_nanos(16_666_650_000));
+    }
+}
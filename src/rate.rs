@@ -1,5 +1,7 @@
 use core::{
-    num::NonZeroU32,
+    fmt::{self, Display, Formatter},
+    num::{IntErrorKind, NonZeroU32, NonZeroU64},
+    str::FromStr,
     time::Duration,
 };
 
@@ -7,17 +9,18 @@ use core::{
 #[derive(Debug, Clone, Copy)]
 pub struct Rate {
     /// Ticks.
-    pub(crate) numerator: NonZeroU32,
+    pub(crate) numerator: NonZeroU64,
     /// Seconds.
-    pub(crate) denominator: NonZeroU32,
+    pub(crate) denominator: NonZeroU64,
     pub(crate) duration_per: Duration,
     /// The fraction of a nanosecond that accumulates each tick. The numerator
     /// is `residual_per` and the *denominator* is `numerator`.
-    pub(crate) residual_per: u32,
+    pub(crate) residual_per: u64,
 }
 
 impl PartialEq for Rate {
     fn eq(&self, other: &Self) -> bool {
+        debug_assert!(self.is_consistent() && other.is_consistent(), "comparing an inconsistent Rate");
         self.numerator == other.numerator && self.denominator == other.denominator
     }
 }
@@ -27,43 +30,278 @@ impl Rate {
     /// denominator is seconds.
     ///
     /// PANICS if the numerator or denominator are zero, or are greater than
-    /// one billion!
+    /// one billion! (If you need bigger numbers than that, see
+    /// [`per_second_u64`](#method.per_second_u64).)
+    ///
+    /// Every `Rate` ticks at most once per nanosecond, so the time between
+    /// ticks is never less than one nanosecond, however a `Rate` was made.
     pub fn per_second(numerator: u32, denominator: u32) -> Rate {
-        assert_ne!(numerator, 0, "The numerator and denominator cannot be zero.");
-        assert_ne!(denominator, 0, "The numerator and denominator cannot be zero.");
-        assert!(numerator <= 1_000_000_000, "The numerator and denominator may not exceed 1,000,000,000.");
-        assert!(denominator <= 1_000_000_000, "The numerator and denominator may not exceed 1,000,000,000.");
-        Self::per_second_nonzero(NonZeroU32::new(numerator).unwrap(), NonZeroU32::new(denominator).unwrap())
+        match Self::try_per_second(numerator, denominator) {
+            Ok(x) => x,
+            Err(x) => panic!("{}", x),
+        }
+    }
+    /// Creates a new Rate with the given numerator and denominator. The
+    /// denominator is seconds.
+    ///
+    /// Like [`per_second`](#method.per_second), but returns an error instead
+    /// of panicking. Use this when the rate comes from somewhere you don't
+    /// control, like a config file.
+    pub fn try_per_second(numerator: u32, denominator: u32) -> Result<Rate, RateError> {
+        if numerator == 0 || denominator == 0 {
+            Err(RateError::Zero)
+        } else if numerator > 1_000_000_000 || denominator > 1_000_000_000 {
+            Err(RateError::TooLarge)
+        } else {
+            Ok(Self::per_second_nonzero(NonZeroU32::new(numerator).unwrap(), NonZeroU32::new(denominator).unwrap()))
+        }
     }
     /// Creates a new Rate with the given numerator and denominator. The
     /// denominator is seconds.
     ///
     /// YOU must ensure that the numerator and denominator do not exceed one
-    /// billion.
+    /// billion. If the numerator is too big, ticks would be less than a
+    /// nanosecond apart, which debug builds catch with a panic.
     pub const fn per_second_nonzero(numerator: NonZeroU32, denominator: NonZeroU32) -> Rate {
-        let numerator_int = numerator.get();
-        let denominator_int = denominator.get();
-        let gcd = gcd(numerator_int, denominator_int);
-        let (numerator_int, denominator_int) = (numerator_int / gcd, denominator_int / gcd);
+        Self::per_second_nonzero_u64(numerator.get() as u64, denominator.get() as u64)
+    }
+    /// Creates a new Rate with the given 64-bit numerator and denominator.
+    /// The denominator is seconds. Timing remains perfectly accurate over any
+    /// span of time, no matter how awkward the fraction.
+    ///
+    /// The one-billion limit of [`per_second`](#method.per_second) is there
+    /// to keep the rate at or below one tick per nanosecond. This function
+    /// enforces that limit directly, so the real bound is that `numerator`
+    /// must not exceed one billion times `denominator` (after reduction).
+    ///
+    /// PANICS if the numerator or denominator are zero, or if the rate is
+    /// faster than one tick per nanosecond!
+    pub fn per_second_u64(numerator: u64, denominator: u64) -> Rate {
+        assert_ne!(numerator, 0, "The numerator and denominator cannot be zero.");
+        assert_ne!(denominator, 0, "The numerator and denominator cannot be zero.");
+        assert!(numerator as u128 <= denominator as u128 * 1_000_000_000, "The rate may not exceed one tick per nanosecond.");
+        Self::per_second_nonzero_u64(numerator, denominator)
+    }
+    /// Creates a new Rate with the given 64-bit numerator and denominator.
+    /// The denominator is seconds.
+    ///
+    /// Like [`per_second_u64`](#method.per_second_u64), but returns an error
+    /// instead of panicking.
+    pub fn try_per_second_u64(numerator: u64, denominator: u64) -> Result<Rate, RateError> {
+        if numerator == 0 || denominator == 0 {
+            Err(RateError::Zero)
+        } else if numerator as u128 > denominator as u128 * 1_000_000_000 {
+            Err(RateError::TooLarge)
+        } else {
+            Ok(Self::per_second_nonzero_u64(numerator, denominator))
+        }
+    }
+    /// Creates a new Rate of `numerator` ticks per `denominator` minutes.
+    ///
+    /// PANICS if the numerator or denominator are zero! (There's no danger
+    /// of going faster than one tick per nanosecond here.)
+    pub fn per_minute(numerator: u32, denominator: u32) -> Rate {
+        Self::per_second_u64(numerator as u64, denominator as u64 * 60)
+    }
+    /// Creates a new Rate of one tick per beat, at the given tempo in beats
+    /// per minute, for rhythm games that want ticks right on the beat. Same
+    /// as `per_minute(beats_per_minute, 1)`.
+    ///
+    /// PANICS if `beats_per_minute` is zero!
+    pub fn bpm(beats_per_minute: u32) -> Rate {
+        Self::per_minute(beats_per_minute, 1)
+    }
+    /// Creates a new Rate of `subdivisions` ticks per beat, at the given tempo
+    /// in beats per minute. For example, `bpm_subdivided(140, 4)` ticks on
+    /// every sixteenth note in 4/4 time at 140 BPM.
+    ///
+    /// PANICS if either argument is zero, or if the result would be faster
+    /// than one tick per nanosecond!
+    pub fn bpm_subdivided(beats_per_minute: u32, subdivisions: u32) -> Rate {
+        Self::per_second_u64(beats_per_minute as u64 * subdivisions as u64, 60)
+    }
+    /// Returns the numerator (ticks) and denominator (seconds) of this rate,
+    /// in lowest terms. Every constructor reduces the fraction, so
+    /// `Rate::per_second(60, 2).reduced()` is `(30, 1)`, and two `Rate`s are
+    /// equal exactly when these are. They're 64-bit because
+    /// [`per_second_u64`](#method.per_second_u64) and
+    /// [`per_minute`](#method.per_minute) can make rates that don't fit in 32.
+    pub fn reduced(&self) -> (u64, u64) {
+        (self.numerator.get(), self.denominator.get())
+    }
+    /// Returns how many whole ticks fit in `duration` at this rate, rounding
+    /// down. This is exact, taking the fraction of a nanosecond in each tick
+    /// into account, so e.g. at `60000/1001` exactly 60000 ticks fit in 1001
+    /// seconds, but only 59999 fit in a nanosecond less. Saturates at
+    /// `u64::MAX`.
+    pub fn ticks_in(&self, duration: Duration) -> u64 {
+        let (numerator, denominator) = (self.numerator.get() as u128, self.denominator.get() as u128);
+        // ticks = (secs + nanos/1G) * numerator / denominator; do the whole
+        // seconds first so that nothing overflows
+        let whole = duration.as_secs() as u128 * numerator;
+        let (quotient, remainder) = (whole / denominator, whole % denominator);
+        let fraction = (remainder * 1_000_000_000 + duration.subsec_nanos() as u128 * numerator)
+            / (denominator * 1_000_000_000);
+        (quotient + fraction).try_into().unwrap_or(u64::MAX)
+    }
+    /// Returns `true` if this `Rate` is in lowest terms, within the limits,
+    /// and its cached tick duration is what its numerator and denominator
+    /// say it should be. Always true for a `Rate` made by any of the
+    /// constructors; this is a sanity check for tests, and for debug builds,
+    /// in which comparing two `Rate`s asserts it.
+    pub fn is_consistent(&self) -> bool {
+        let (numerator, denominator) = (self.numerator.get(), self.denominator.get());
+        if gcd(numerator, denominator) != 1
+        || numerator as u128 > denominator as u128 * 1_000_000_000 {
+            return false
+        }
+        let expected = Self::per_second_nonzero_u64(numerator, denominator);
+        self.duration_per == expected.duration_per && self.residual_per == expected.residual_per
+    }
+    /// Returns how many whole nanoseconds one tick at this rate lasts, which
+    /// is how many distinct phases a frame can have between two ticks, since
+    /// phase is worked out to the nanosecond. If this is small (say, with
+    /// very fast ticks, or a slow-motion time scale stretching each tick
+    /// across many frames), phase will visibly step. Saturates at
+    /// `u64::MAX`.
+    pub fn phase_resolution(&self) -> u64 {
+        self.duration_per.as_nanos().try_into().unwrap_or(u64::MAX)
+    }
+    const fn per_second_nonzero_u64(numerator: u64, denominator: u64) -> Rate {
+        let gcd = gcd(numerator, denominator);
+        let (numerator_int, denominator_int) = (numerator / gcd, denominator / gcd);
         // numerator/denominator = ticks/second
         // 1G*denominator/numerator = nanoseconds/tick
-        let denominator_in_nanoseconds = 1_000_000_000u64 * denominator_int as u64;
+        let denominator_in_nanoseconds = 1_000_000_000u128 * denominator_int as u128;
         let number_of_nanoseconds
-            = denominator_in_nanoseconds / (numerator_int as u64);
+            = denominator_in_nanoseconds / (numerator_int as u128);
         let residual
-            = denominator_in_nanoseconds % (numerator_int as u64);
-        debug_assert!(residual < u32::MAX as u64);
+            = denominator_in_nanoseconds % (numerator_int as u128);
+        // Everything that divides by the tick period relies on this
+        debug_assert!(number_of_nanoseconds != 0, "The rate may not exceed one tick per nanosecond.");
         Rate {
-            numerator: unsafe { NonZeroU32::new_unchecked(numerator_int) },
-            denominator: unsafe { NonZeroU32::new_unchecked(denominator_int) },
-            duration_per: Duration::from_nanos(number_of_nanoseconds),
-            residual_per: residual as u32,
+            numerator: unsafe { NonZeroU64::new_unchecked(numerator_int) },
+            denominator: unsafe { NonZeroU64::new_unchecked(denominator_int) },
+            duration_per: Duration::new(
+                (number_of_nanoseconds / 1_000_000_000) as u64,
+                (number_of_nanoseconds % 1_000_000_000) as u32,
+            ),
+            residual_per: residual as u64,
+        }
+    }
+}
+
+/// Makes a `const` [`Rate`](struct.Rate.html) from a literal fraction, e.g.
+/// `rate!(60000 / 1001)`, or just a numerator, e.g. `rate!(30)`, for a
+/// denominator of 1. The limits of
+/// [`Rate::per_second`](struct.Rate.html#method.per_second) are checked at
+/// compile time, so this never panics, and needs no `unsafe`.
+///
+/// ```rust
+/// # use ftvf::*;
+/// const NTSC: Rate = rate!(60000 / 1001);
+/// assert_eq!(NTSC, Rate::per_second(60000, 1001));
+/// ```
+///
+/// A zero, or a number over one billion, is a compile error:
+///
+/// ```rust,compile_fail
+/// # use ftvf::*;
+/// const NEVER: Rate = rate!(0 / 1);
+/// ```
+///
+/// ```rust,compile_fail
+/// # use ftvf::*;
+/// const TOO_FAST: Rate = rate!(2_000_000_000 / 1);
+/// ```
+#[macro_export]
+macro_rules! rate {
+    ($numerator:literal / $denominator:literal) => {{
+        const RATE: $crate::Rate = {
+            const NUMERATOR: u32 = $numerator;
+            const DENOMINATOR: u32 = $denominator;
+            ::core::assert!(NUMERATOR != 0 && DENOMINATOR != 0,
+                "The numerator and denominator cannot be zero.");
+            ::core::assert!(NUMERATOR <= 1_000_000_000 && DENOMINATOR <= 1_000_000_000,
+                "The numerator and denominator may not exceed 1,000,000,000.");
+            match (::core::num::NonZeroU32::new(NUMERATOR), ::core::num::NonZeroU32::new(DENOMINATOR)) {
+                (::core::option::Option::Some(numerator), ::core::option::Option::Some(denominator))
+                    => $crate::Rate::per_second_nonzero(numerator, denominator),
+                _ => ::core::unreachable!(),
+            }
+        };
+        RATE
+    }};
+    ($numerator:literal) => { $crate::rate!($numerator / 1) };
+}
+
+/// Displays the rate in lowest terms, as `numerator/denominator`, e.g.
+/// `60000/1001` or `30/1`. This is the same form `parse` accepts.
+impl Display for Rate {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+/// Parses `numerator/denominator` (e.g. `60000/1001`), or just `numerator`
+/// (e.g. `30`) for a denominator of 1. Subject to the same limits as
+/// [`try_per_second_u64`](#method.try_per_second_u64), so anything `Display`
+/// prints can be parsed back.
+impl FromStr for Rate {
+    type Err = RateError;
+    fn from_str(s: &str) -> Result<Rate, RateError> {
+        fn parse_part(s: &str) -> Result<u64, RateError> {
+            s.trim().parse().map_err(|x: core::num::ParseIntError| match x.kind() {
+                IntErrorKind::PosOverflow => RateError::TooLarge,
+                _ => RateError::Malformed,
+            })
         }
+        let (numerator, denominator) = match s.split_once('/') {
+            Some((numerator, denominator)) => (parse_part(numerator)?, parse_part(denominator)?),
+            None => (parse_part(s)?, 1),
+        };
+        Rate::try_per_second_u64(numerator, denominator)
+    }
+}
+
+/// Why [`Rate::try_per_second`](struct.Rate.html#method.try_per_second), or
+/// parsing a `Rate`, failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateError {
+    /// The numerator or denominator was zero.
+    Zero,
+    /// The numerator or denominator was greater than one billion, or (for
+    /// 64-bit rates) the rate was faster than one tick per nanosecond.
+    TooLarge,
+    /// The string being parsed wasn't `numerator/denominator` or
+    /// `numerator`.
+    Malformed,
+}
+
+impl Display for RateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RateError::Zero => write!(f, "The numerator and denominator cannot be zero."),
+            RateError::TooLarge => write!(f, "The rate is too large. The numerator and denominator may not exceed 1,000,000,000, and the rate may not exceed one tick per nanosecond."),
+            RateError::Malformed => write!(f, "A rate must be written as a whole number, or as two whole numbers separated by a slash."),
+        }
+    }
+}
+
+#[cfg(not(feature="no_std"))]
+impl std::error::Error for RateError {}
+
+impl From<(u32, u32)> for Rate {
+    /// Converts a `(numerator, denominator)` tuple, the way rates were passed
+    /// before 0.6, into a `Rate`. Calls [`per_second`](#method.per_second),
+    /// and PANICS under the same conditions.
+    fn from((numerator, denominator): (u32, u32)) -> Rate {
+        Rate::per_second(numerator, denominator)
     }
 }
 
 /// Compute the greatest common divisor of two numbers using Lamé's method.
-const fn gcd(a: u32, b: u32) -> u32 {
+pub(crate) const fn gcd(a: u64, b: u64) -> u64 {
     let (mut big, mut small) = if a > b { (a,b) } else { (b,a) };
     while big != small {
         big = big % small;
@@ -79,6 +317,57 @@ mod test {
     #[cfg(feature="no_std")]
     use std::prelude::*;
     #[test]
+    fn consistency() {
+        for rate in [Rate::per_second(60000, 1001), Rate::per_second(64, 288), rate!(30), Rate::bpm_subdivided(140, 4)] {
+            assert!(rate.is_consistent());
+        }
+        let good = Rate::per_second(30, 1);
+        assert!(!Rate { duration_per: Duration::from_millis(33), ..good }.is_consistent());
+        assert!(!Rate { residual_per: 0, ..Rate::per_second(60000, 1001) }.is_consistent());
+        let unreduced = Rate { numerator: NonZeroU64::new(60).unwrap(), denominator: NonZeroU64::new(2).unwrap(), ..good };
+        assert!(!unreduced.is_consistent());
+    }
+    #[test]
+    fn phase_resolution() {
+        assert_eq!(Rate::per_second(1000, 1).phase_resolution(), 1_000_000);
+        assert_eq!(Rate::per_second(60000, 1001).phase_resolution(), 16_683_333);
+        assert_eq!(Rate::per_second(1_000_000_000, 1).phase_resolution(), 1);
+    }
+    #[test]
+    fn extremes() {
+        let fastest = Rate::per_second(1_000_000_000, 1);
+        assert_eq!((fastest.duration_per, fastest.residual_per), (Duration::from_nanos(1), 0));
+        let almost = Rate::per_second(999_999_999, 1);
+        assert_eq!((almost.duration_per, almost.residual_per), (Duration::from_nanos(1), 1));
+        let slowest = Rate::per_second(1, 1_000_000_000);
+        assert_eq!(slowest.duration_per, Duration::from_secs(1_000_000_000));
+        let fastest_u64 = Rate::per_second_u64(u64::MAX, u64::MAX / 1_000_000_000 + 1);
+        for rate in [fastest, almost, slowest, fastest_u64, Rate::per_second_u64(u64::MAX, 1 << 63)] {
+            assert!(!rate.duration_per.is_zero(), "{:?}", rate);
+            assert!(rate.is_consistent());
+        }
+        // A billion ticks a second, for a second: no division by zero, and
+        // not a tick more or less.
+        let start = crate::PreciseInstant::from(Duration::ZERO);
+        assert_eq!(start.ticks_until(&Duration::from_secs(1), &fastest), 1_000_000_000);
+        assert_eq!(start.nth(999_999_999, &almost).unwrap().at, Duration::from_secs(1));
+    }
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic]
+    fn unchecked_faster_than_a_nanosecond() {
+        let _ = Rate::per_second_nonzero(NonZeroU32::new(2_000_000_000).unwrap(), NonZeroU32::new(1).unwrap());
+    }
+    #[test]
+    fn rate_macro() {
+        const NTSC: Rate = rate!(60000 / 1001);
+        assert_eq!(NTSC, Rate::per_second(60000, 1001));
+        assert_eq!(NTSC.duration_per, Rate::per_second(60000, 1001).duration_per);
+        assert_eq!(NTSC.residual_per, Rate::per_second(60000, 1001).residual_per);
+        assert_eq!(rate!(30), Rate::per_second(30, 1));
+        assert_eq!(rate!(1_000_000_000 / 1_000_000_000), Rate::per_second(1, 1));
+    }
+    #[test]
     fn gcdtest() {
         let test_set = [
             (60000, 1001, 1),
@@ -89,4 +378,139 @@ mod test {
             assert_eq!(gcd(a,b), answer);
         }
     }
+    #[test]
+    fn reduced() {
+        // gcd(114411, 258522) = 33, per `gcdtest`
+        assert_eq!(Rate::per_second(114411, 258522).reduced(), (114411 / 33, 258522 / 33));
+        assert_eq!(Rate::per_second(60, 2).reduced(), (30, 1));
+        assert_eq!(Rate::per_second(60000, 1001).reduced(), (60000, 1001));
+        assert_eq!(Rate::per_minute(120, 1).reduced(), (2, 1));
+        assert_eq!(Rate::per_minute(1, u32::MAX).reduced(), (1, u32::MAX as u64 * 60));
+    }
+    #[test]
+    fn from_tuple() {
+        assert_eq!(Rate::from((60, 1)), Rate::per_second(60, 1));
+        let rate: Rate = (60000, 1001).into();
+        assert_eq!(rate, Rate::per_second(60000, 1001));
+    }
+    #[test]
+    #[should_panic]
+    fn from_bad_tuple() {
+        let _: Rate = (0, 1).into();
+    }
+    #[test]
+    fn try_per_second() {
+        assert_eq!(Rate::try_per_second(60000, 1001), Ok(Rate::per_second(60000, 1001)));
+        assert_eq!(Rate::try_per_second(1_000_000_000, 1_000_000_000), Ok(Rate::per_second(1, 1)));
+        assert_eq!(Rate::try_per_second(0, 1), Err(RateError::Zero));
+        assert_eq!(Rate::try_per_second(1, 0), Err(RateError::Zero));
+        assert_eq!(Rate::try_per_second(1_000_000_001, 1), Err(RateError::TooLarge));
+        assert_eq!(Rate::try_per_second(1, 1_000_000_001), Err(RateError::TooLarge));
+        assert_eq!(Rate::try_per_second_u64(5_000_000_000, 7), Ok(Rate::per_second_u64(5_000_000_000, 7)));
+        assert_eq!(Rate::try_per_second_u64(0, 1), Err(RateError::Zero));
+        assert_eq!(Rate::try_per_second_u64(1_000_000_001, 1), Err(RateError::TooLarge));
+    }
+    #[test]
+    fn parse() {
+        assert_eq!("30".parse(), Ok(Rate::per_second(30, 1)));
+        assert_eq!("60000/1001".parse(), Ok(Rate::per_second(60000, 1001)));
+        assert_eq!(" 120 / 2 ".parse(), Ok(Rate::per_second(60, 1)));
+        assert_eq!("0/1".parse::<Rate>(), Err(RateError::Zero));
+        assert_eq!("1/0".parse::<Rate>(), Err(RateError::Zero));
+        assert_eq!("1000000001".parse::<Rate>(), Err(RateError::TooLarge));
+        assert_eq!("99999999999/1".parse::<Rate>(), Err(RateError::TooLarge));
+        assert_eq!("99999999999999999999".parse::<Rate>(), Err(RateError::TooLarge));
+        for bad in ["abc", "", "/", "30/", "/30", "1/2/3", "-5", "2.5"] {
+            assert_eq!(bad.parse::<Rate>(), Err(RateError::Malformed), "{:?}", bad);
+        }
+    }
+    #[test]
+    fn display() {
+        for (rate, string) in [
+            (Rate::per_second(60000, 1001), "60000/1001"),
+            (Rate::per_second(30, 1), "30/1"),
+            (Rate::per_second(120, 2), "60/1"),
+            (Rate::per_second_u64(5_000_000_000, 7), "5000000000/7"),
+            (Rate::per_second_u64(1, 5_000_000_000), "1/5000000000"),
+        ] {
+            assert_eq!(format!("{}", rate), string);
+            assert_eq!(string.parse(), Ok(rate));
+        }
+    }
+    #[test]
+    fn sixty_four_bits() {
+        use crate::PreciseInstant;
+        // A tick per sidereal second, give or take: ~1002.7 ticks per second,
+        // with a numerator far too big for `u32`.
+        let rate = Rate::per_second_u64(864_000_000_000, 861_640_905);
+        assert_eq!(rate.numerator.get(), 57_600_000_000);
+        assert_eq!(rate.denominator.get(), 57_442_727);
+        assert_eq!(rate.duration_per, Duration::from_nanos(997_269));
+        // After exactly `numerator` ticks, exactly `denominator` seconds must
+        // have passed, with no residual left over.
+        let mut instant = PreciseInstant::from(Duration::ZERO);
+        for _ in 0 .. 24 {
+            instant = instant.nth(2_400_000_000, &rate).unwrap();
+        }
+        assert_eq!(instant.at, Duration::from_secs(57_442_727));
+        assert_eq!(instant.residual, 0);
+        // And single steps must agree with big ones.
+        let mut stepped = PreciseInstant::from(Duration::ZERO);
+        for _ in 0 .. 12345 {
+            stepped = stepped.next(&rate).unwrap();
+        }
+        let jumped = PreciseInstant::from(Duration::ZERO).nth(12345, &rate).unwrap();
+        assert_eq!(stepped.at, jumped.at);
+        assert_eq!(stepped.residual, jumped.residual);
+    }
+    #[test]
+    fn per_minute() {
+        assert_eq!(Rate::bpm(120), Rate::per_second(2, 1));
+        assert_eq!(Rate::bpm(140), Rate::per_second(7, 3));
+        assert_eq!(Rate::per_minute(90, 2), Rate::per_second(3, 4));
+        assert_eq!(Rate::bpm_subdivided(120, 4), Rate::per_second(8, 1));
+        assert_eq!(Rate::bpm_subdivided(140, 4).ticks_in(Duration::from_secs(3)), 28);
+        // A beat at 140 BPM is 3/7 of a second, which isn't a whole number
+        // of nanoseconds.
+        let rate = Rate::bpm(140);
+        assert_eq!(rate.duration_per, Duration::from_nanos(428_571_428));
+        assert_eq!(rate.residual_per, 4);
+        assert_eq!(Rate::bpm(u32::MAX).duration_per, Duration::from_nanos(13));
+    }
+    #[test]
+    #[should_panic]
+    fn zero_bpm() {
+        Rate::bpm(0);
+    }
+    #[test]
+    #[should_panic]
+    fn too_subdivided() {
+        Rate::bpm_subdivided(u32::MAX, u32::MAX);
+    }
+    #[test]
+    fn ticks_in() {
+        let rate = Rate::per_second(60000, 1001);
+        assert_eq!(rate.ticks_in(Duration::ZERO), 0);
+        assert_eq!(rate.ticks_in(Duration::from_secs(1)), 59);
+        assert_eq!(rate.ticks_in(Duration::from_secs(1001)), 60000);
+        assert_eq!(rate.ticks_in(Duration::from_secs(1001) - Duration::from_nanos(1)), 59999);
+        // Sixty whole `duration_per`s fall short of sixty ticks, by the
+        // residual.
+        assert_eq!(rate.ticks_in(rate.duration_per * 60), 59);
+        assert_eq!(rate.ticks_in(Duration::from_millis(1001)), 60);
+        assert_eq!(Rate::per_second(1_000_000_000, 1).ticks_in(Duration::MAX), u64::MAX);
+        assert_eq!(Rate::per_second(1, 1).ticks_in(Duration::MAX), u64::MAX);
+    }
+    #[test]
+    fn slower_than_u32_allows() {
+        // Once every ten billion and one seconds.
+        let rate = Rate::per_second_u64(1, 10_000_000_001);
+        assert_eq!(rate.duration_per, Duration::from_secs(10_000_000_001));
+        assert_eq!(rate.residual_per, 0);
+    }
+    #[test]
+    #[should_panic]
+    fn faster_than_a_nanosecond() {
+        Rate::per_second_u64(2_000_000_001, 2);
+    }
 }
\ No newline at end of file
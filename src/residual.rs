@@ -12,7 +12,7 @@ use super::{Rate, TemporalSample};
 #[derive(Debug, Clone)]
 pub(crate) struct PreciseInstant<Instant: TemporalSample> {
     pub(crate) at: Instant,
-    pub(crate) residual: u32,
+    pub(crate) residual: u64,
 }
 
 impl<Instant: TemporalSample> From<Instant> for PreciseInstant<Instant> {
@@ -34,23 +34,41 @@ impl<Instant: TemporalSample> PartialOrd for PreciseInstant<Instant> {
 }
 
 impl<Instant: TemporalSample> PreciseInstant<Instant> {
-    pub(crate) fn next(&self, rate: &Rate) -> Self {
-        let at = self.at.advanced_by(rate.duration_per);
-        let residual = self.residual + rate.residual_per;
-        if residual >= rate.numerator.get() {
-            let residual = residual - rate.numerator.get();
-            debug_assert!(residual < rate.numerator.get());
-            Self { at: at.advanced_by(Duration::from_nanos(1)), residual }
-        } else { Self { at, residual } }
+    /// The next tick. `None` if the underlying `TemporalSample` can't go
+    /// that far.
+    pub(crate) fn next(&self, rate: &Rate) -> Option<Self> {
+        let at = self.at.checked_advanced_by(rate.duration_per)?;
+        let numerator = rate.numerator.get() as u128;
+        let residual = self.residual as u128 + rate.residual_per as u128;
+        if residual >= numerator {
+            let residual = residual - numerator;
+            debug_assert!(residual < numerator);
+            Some(Self { at: at.checked_advanced_by(Duration::from_nanos(1))?, residual: residual as u64 })
+        } else { Some(Self { at, residual: residual as u64 }) }
     }
-    pub(crate) fn nth(&self, n: u32, rate: &Rate) -> Self {
-        let at = self.at.advanced_by(rate.duration_per * n);
-        let residual = self.residual as u64 + rate.residual_per as u64 * n as u64;
-        if residual >= rate.numerator.get() as u64 {
-            let advance_by = residual / rate.numerator.get() as u64;
-            let residual = residual % rate.numerator.get() as u64;
-            Self { at: at.advanced_by(Duration::from_nanos(advance_by)), residual: residual as u32 }
-        } else { Self { at, residual: residual as u32 } }
+    /// The tick `n` ticks from now. `None` if the underlying `TemporalSample`
+    /// (or a `Duration`) can't go that far.
+    pub(crate) fn nth(&self, n: u32, rate: &Rate) -> Option<Self> {
+        let at = self.at.checked_advanced_by(rate.duration_per.checked_mul(n)?)?;
+        let numerator = rate.numerator.get() as u128;
+        let residual = self.residual as u128 + rate.residual_per as u128 * n as u128;
+        if residual >= numerator {
+            let advance_by = residual / numerator;
+            let residual = residual % numerator;
+            Some(Self { at: at.checked_advanced_by(Duration::from_nanos(advance_by as u64))?, residual: residual as u64 })
+        } else { Some(Self { at, residual: residual as u64 }) }
+    }
+    /// As `nth`, but `n` ticks into the past. `None` if the underlying
+    /// `TemporalSample` can't go back that far.
+    pub(crate) fn nth_back(&self, n: u32, rate: &Rate) -> Option<Self> {
+        let numerator = rate.numerator.get() as u128;
+        let owed = rate.residual_per as u128 * n as u128;
+        // borrow whole nanoseconds until the residual can pay what's owed
+        let borrow = owed.saturating_sub(self.residual as u128).div_ceil(numerator);
+        let residual = self.residual as u128 + borrow * numerator - owed;
+        let rewind = rate.duration_per.checked_mul(n)?
+            .checked_add(Duration::from_nanos(borrow.try_into().ok()?))?;
+        Some(Self { at: self.at.rewound_by(rewind)?, residual: residual as u64 })
     }
     // approximate!
     pub(crate) fn ticks_until(&self, target_time: &Instant, rate: &Rate) -> u32 {
@@ -63,9 +81,46 @@ impl<Instant: TemporalSample> PreciseInstant<Instant> {
     }
     pub(crate) fn last_tick_before(&self, target_time: &Instant, rate: &Rate) -> PreciseInstant<Instant> {
         let surplus = self.ticks_until(target_time, rate);
+        // The last tick before a representable instant should be
+        // representable too, but `ticks_until` is approximate.
         self.nth(surplus, rate)
+            .unwrap_or_else(|| PreciseInstant::from(target_time.clone()))
     }
     pub(crate) fn forget_residual(&mut self) {
         self.residual = 0;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn slow_catch_up() {
+        // Once per hour, skipping as many ticks as a `u32` can count.
+        let rate = Rate::per_second(1, 3600);
+        let start = PreciseInstant::from(Duration::ZERO);
+        let end = start.nth(u32::MAX, &rate).unwrap();
+        assert_eq!(end.at, Duration::from_secs(3600 * u32::MAX as u64));
+    }
+    #[test]
+    fn back_and_forth() {
+        let rate = Rate::per_second(60000, 1001);
+        let start = PreciseInstant::from(Duration::from_secs(1)).nth(7, &rate).unwrap();
+        for n in [0, 1, 2, 3, 59, 60, 61, 1000, 60000] {
+            let there = start.nth(n, &rate).unwrap();
+            let back = there.nth_back(n, &rate).unwrap();
+            assert_eq!(back.at, start.at, "n = {}", n);
+            assert_eq!(back.residual, start.residual, "n = {}", n);
+        }
+        assert!(start.nth_back(60000, &rate).is_none());
+    }
+    #[test]
+    fn overflowing_catch_up() {
+        // Once every ten quintillion seconds. Two of those don't fit in a
+        // `Duration`.
+        let rate = Rate::per_second_u64(1, 10_000_000_000_000_000_000);
+        let start = PreciseInstant::from(Duration::ZERO);
+        assert_eq!(start.nth(1, &rate).unwrap().at, Duration::from_secs(10_000_000_000_000_000_000));
+        assert!(start.nth(2, &rate).is_none());
+        assert!(start.nth(u32::MAX, &rate).is_none());
+    }
+}
@@ -1,10 +1,9 @@
 
 #[cfg(feature="no_std")]
-use std::prelude::*;
+use std::prelude::rust_2021::*;
 
 use std::{
     cell::RefCell,
-    num::NonZeroU32,
     time::Duration,
 };
 
@@ -17,6 +16,10 @@ enum TestCmd<'a> {
     SetTickrate(u32, u32),
 }
 use TestCmd::*;
+const fn tick(nanos: u64) -> Reading {
+    Reading::Tick { dt: Duration::from_nanos(nanos) }
+}
+#[allow(dead_code)]
 #[derive(Copy,Clone,Default,Debug,PartialOrd,PartialEq)]
 struct TestInstant(Duration);
 impl TemporalSample for TestInstant {
@@ -27,18 +30,77 @@ impl TemporalSample for TestInstant {
         TestInstant(self.0 + amount)
     }
 }
+/// A sixteen-bit millisecond counter, like a small microcontroller might
+/// have, plus the sub-millisecond residual `TemporalSample` requires. Wraps
+/// every 65.536 seconds.
+#[derive(Copy,Clone,Debug,PartialEq)]
+struct Wrapping16Instant { millis: u16, nanos: u32 }
+impl PartialOrd for Wrapping16Instant {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        use core::cmp::Ordering;
+        Some(if self == other { Ordering::Equal }
+        else if self.time_since(other).is_some() { Ordering::Greater }
+        else { Ordering::Less })
+    }
+}
+impl TemporalSample for Wrapping16Instant {
+    fn time_since(&self, origin: &Self) -> Option<Duration> {
+        let millis = self.millis.wrapping_sub(origin.millis);
+        let nanos = millis as i64 * 1_000_000 + self.nanos as i64 - origin.nanos as i64;
+        // More than half a wrap ahead? Then it's really behind.
+        if millis >= 0x8000 || nanos < 0 { None }
+        else { Some(Duration::from_nanos(nanos as u64)) }
+    }
+    fn advanced_by(&self, amount: Duration) -> Self {
+        let nanos = self.nanos as u128 + amount.as_nanos();
+        Wrapping16Instant {
+            millis: self.millis.wrapping_add((nanos / 1_000_000) as u16),
+            nanos: (nanos % 1_000_000) as u32,
+        }
+    }
+}
+struct Wrapping16NowSource<'a>(&'a core::cell::Cell<u16>);
+impl NowSource for Wrapping16NowSource<'_> {
+    type Instant = Wrapping16Instant;
+    fn now(&mut self) -> Wrapping16Instant {
+        Wrapping16Instant { millis: self.0.get(), nanos: 0 }
+    }
+}
+/// Nanoseconds in a `u64`, wrapping (wrongly) on overflow.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+struct BoundedInstant(u64);
+impl TemporalSample for BoundedInstant {
+    fn time_since(&self, origin: &Self) -> Option<Duration> {
+        self.0.checked_sub(origin.0).map(Duration::from_nanos)
+    }
+    fn advanced_by(&self, amount: Duration) -> Self {
+        BoundedInstant(self.0.wrapping_add(amount.as_nanos() as u64))
+    }
+    fn checked_advanced_by(&self, amount: Duration) -> Option<Self> {
+        Some(BoundedInstant(self.0.checked_add(amount.as_nanos().try_into().ok()?)?))
+    }
+}
+struct BoundedNowSource<'a>(&'a core::cell::Cell<u64>);
+impl NowSource for BoundedNowSource<'_> {
+    type Instant = BoundedInstant;
+    fn now(&mut self) -> BoundedInstant {
+        BoundedInstant(self.0.get())
+    }
+}
 fn run_test(tps: (u32, u32), max_ticks_behind: u32, cmds: &[TestCmd]) {
     let now_source = RefCell::new(FakeNowSource::default());
     let mut metronome = Metronome::new(&now_source, Rate::per_second(tps.0, tps.1), max_ticks_behind);
     let mut bad = None;
-    for n in 0..cmds.len() {
-        let cmd = &cmds[n];
+    for (n, cmd) in cmds.iter().enumerate() {
         match cmd {
             SetNow(sec, nsec) => {
                 now_source.borrow_mut().now = Duration::new(*sec,*nsec);
             },
             Sample(mode, readings) => {
-                let check: Vec<Reading> = metronome.sample(*mode).collect();
+                let readings_iter = metronome.sample(*mode);
+                let (_, upper) = readings_iter.size_hint();
+                let check: Vec<Reading> = readings_iter.collect();
+                assert!(upper.unwrap() >= check.len(), "size_hint {:?} < {}", upper, check.len());
                 if &check[..] != *readings {
                     bad = Some((n, format!("got {:?}", check)));
                     break;
@@ -51,8 +113,8 @@ fn run_test(tps: (u32, u32), max_ticks_behind: u32, cmds: &[TestCmd]) {
     }
     if let Some((index, explanation)) = bad {
         eprintln!("Test failed!");
-        for n in index.saturating_sub(10) .. index {
-            eprintln!("OK\t[{}] = {:?}", n, cmds[n]);
+        for (n, cmd) in cmds.iter().enumerate().take(index).skip(index.saturating_sub(10)) {
+            eprintln!("OK\t[{}] = {:?}", n, cmd);
         }
         eprintln!("BAD\t[{}] = {:?}", index, cmds[index]);
         eprintln!("{}", explanation);
@@ -66,46 +128,46 @@ fn simple() {
     ];
     run_test((5, 1), 10, &[
         Sample(Mode::OneFramePerTick, &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(200_000_000),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         Sample(Mode::OneFramePerTick, IDLE_FIFTH_SECOND),
         SetNow(1, 0),
         Sample(Mode::UnlimitedFrames, &[
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(200_000_000),
+            tick(200_000_000),
+            tick(200_000_000),
+            tick(200_000_000),
+            tick(200_000_000),
+            Reading::Frame { phase: 1.0, interpolating: true, on_tick_boundary: true, output_id: 0 },
         ]),
         Sample(Mode::UnlimitedFrames, &[
         ]),
         SetNow(2, 0),
         Sample(Mode::TickOnly, &[
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
+            tick(200_000_000),
+            tick(200_000_000),
+            tick(200_000_000),
+            tick(200_000_000),
+            tick(200_000_000),
         ]),
         Sample(Mode::TickOnly, IDLE_FIFTH_SECOND),
         SetNow(2, 100000000),
         Sample(Mode::UnlimitedFrames, &[
-            Reading::Tick,
-            Reading::Frame { phase: 0.5 },
+            tick(200_000_000),
+            Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
         ]),
         Sample(Mode::UnlimitedFrames, &[
         ]),
         SetNow(2, 200000000),
         Sample(Mode::UnlimitedFrames, &[
-            Reading::Frame { phase: 1.0 },
+            Reading::Frame { phase: 1.0, interpolating: true, on_tick_boundary: true, output_id: 0 },
         ]),
         SetNow(1, 0),
         Sample(Mode::UnlimitedFrames, &[
             Reading::TimeWentBackwards,
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(200_000_000),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
     ]);
 }
@@ -113,66 +175,66 @@ fn simple() {
 fn ntsc() {
     run_test((60000, 1001), 120, &[
         Sample(Mode::UnlimitedFrames, &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(16_683_333),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         SetNow(0, 500000000),
         Sample(Mode::UnlimitedFrames, &[
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Frame { phase: 0.97002995 }, // roughly 30.0 / 1.001 - 29.0
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            tick(16_683_333),
+            tick(16_683_334),
+            tick(16_683_333),
+            Reading::Frame { phase: 0.97002995, interpolating: true, on_tick_boundary: false, output_id: 0 }, // roughly 30.0 / 1.001 - 29.0
         ]),
     ]);
 }
 #[test]
 fn marathon() {
-    const SIXTY_FPS: Rate = unsafe { Rate::per_second_nonzero(NonZeroU32::new_unchecked(60), NonZeroU32::new_unchecked(1)) };
+    const SIXTY_FPS: Rate = rate!(60 / 1);
     run_test((30, 1), 94332, &[
         Sample(Mode::TargetFramesPerSecond(SIXTY_FPS), &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(33_333_333),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         SetNow(0, 1000000000 * 2 / 60),
         Sample(Mode::TargetFramesPerSecond(SIXTY_FPS), &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(33_333_333),
+            Reading::Frame { phase: 1.0, interpolating: true, on_tick_boundary: true, output_id: 0 },
         ]),
         SetNow(0, 1000000000 * 3 / 60),
         Sample(Mode::TargetFramesPerSecond(SIXTY_FPS), &[
-            Reading::Tick,
-            Reading::Frame { phase: 0.50000006 },
+            tick(33_333_334),
+            Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
         ]),
         SetNow(0, 1000000000 * 4 / 60),
         Sample(Mode::TargetFramesPerSecond(SIXTY_FPS), &[
-            Reading::Frame { phase: 1.0 },
+            Reading::Frame { phase: 1.0, interpolating: true, on_tick_boundary: true, output_id: 0 },
         ]),
     ]);
 }
@@ -180,21 +242,21 @@ fn marathon() {
 fn residual_tick() {
     run_test((3,1), 444, &[
         Sample(Mode::OneFramePerTick, &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(333_333_333),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         SetNow(0, 500000000),
         Sample(Mode::OneFramePerTick, &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(333_333_333),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         Sample(Mode::OneFramePerTick, &[
             Reading::Idle { duration: Duration::from_nanos(166666666) },
         ]),
         SetNow(0, 750000000),
         Sample(Mode::OneFramePerTick, &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(333_333_334),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         Sample(Mode::OneFramePerTick, &[
             Reading::Idle { duration: Duration::from_nanos(250000000) },
@@ -205,20 +267,1822 @@ fn residual_tick() {
 fn max_payne() {
     run_test((1,1), 2345, &[
         Sample(Mode::OneFramePerTick, &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(1_000_000_000),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         SetNow(1, 0),
         Sample(Mode::OneFramePerTick, &[
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            tick(1_000_000_000),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
         ]),
         SetTickrate(2, 1),
         SetNow(2, 0),
         Sample(Mode::OneFramePerTick, &[
-            Reading::Tick,
-            Reading::Tick,
-            Reading::Frame { phase: 1.0 },
+            Reading::TickrateChanged { new: Rate::per_second(2, 1) },
+            tick(500_000_000),
+            tick(500_000_000),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+        ]),
+    ]);
+}
+#[test]
+fn tickrate_change_phase() {
+    run_test((1,1), 10, &[
+        Sample(Mode::UnlimitedFrames, &[
+            tick(1_000_000_000),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+        ]),
+        SetNow(0, 500000000),
+        Sample(Mode::UnlimitedFrames, &[
+            tick(1_000_000_000),
+            Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
+        ]),
+        SetTickrate(4, 1),
+        // Still between the ticks at 0 and 1 seconds, which were a whole
+        // second apart, even though ticks are now a quarter second apart.
+        SetNow(0, 750000000),
+        Sample(Mode::UnlimitedFrames, &[
+            Reading::Frame { phase: 0.75, interpolating: true, on_tick_boundary: false, output_id: 0 },
+        ]),
+        // Now between the ticks at 1 and 1.25 seconds.
+        SetNow(1, 100000000),
+        Sample(Mode::UnlimitedFrames, &[
+            Reading::TickrateChanged { new: Rate::per_second(4, 1) },
+            tick(250_000_000),
+            Reading::Frame { phase: 0.4, interpolating: true, on_tick_boundary: false, output_id: 0 },
         ]),
+        SetNow(1, 250000000),
+        Sample(Mode::UnlimitedFrames, &[
+            Reading::Frame { phase: 1.0, interpolating: true, on_tick_boundary: true, output_id: 0 },
+        ]),
+    ]);
+}
+#[test]
+fn coalesce_ticks() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).coalesce_ticks().collect::<Vec<_>>(), &[
+        Reading::Ticks { count: 1 },
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).coalesce_ticks().collect::<Vec<_>>(), &[
+        Reading::Idle { duration: Duration::from_millis(100) },
+    ]);
+    now_source.borrow_mut().now = Duration::from_millis(500);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).coalesce_ticks().collect::<Vec<_>>(), &[
+        Reading::Ticks { count: 5 },
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    now_source.borrow_mut().now = Duration::from_millis(1500);
+    assert_eq!(metronome.sample(Mode::TickOnly).coalesce_ticks().collect::<Vec<_>>(), &[
+        Reading::Ticks { count: 5 },
+        Reading::TicksLost,
+    ]);
+}
+#[test]
+fn strict() {
+    fn first_batch(strict: bool, tps: u32, fps: u32) -> Vec<Reading> {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(tps, 1), 10);
+        metronome.set_strict(strict);
+        metronome.sample(Mode::TargetFramesPerSecond(Rate::per_second(fps, 1))).collect()
+    }
+    let normal = [
+        tick(1_000_000_000 / 120),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ];
+    assert_eq!(first_batch(false, 120, 1), &normal);
+    assert_eq!(first_batch(true, 120, 60), &normal);
+    assert_eq!(first_batch(true, 120, 1)[0], Reading::FramerateMismatch);
+    assert_eq!(first_batch(true, 1, 1001)[0], Reading::FramerateMismatch);
+    assert_eq!(first_batch(true, 1, 1000)[0], tick(1_000_000_000));
+    // Only reported once.
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(120, 1), 10);
+    metronome.set_strict(true);
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(1, 1));
+    assert_eq!(metronome.sample(mode).next(), Some(Reading::FramerateMismatch));
+    now_source.borrow_mut().now = Duration::from_secs(1);
+    assert!(!metronome.sample(mode).any(|x| x == Reading::FramerateMismatch));
+}
+#[test]
+fn wrapping_16_bit() {
+    let at = |millis, nanos| Wrapping16Instant { millis, nanos };
+    assert_eq!(at(5, 0).time_since(&at(65530, 0)), Some(Duration::from_millis(11)));
+    assert_eq!(at(65530, 0).time_since(&at(5, 0)), None);
+    assert_eq!(at(65535, 999_999).advanced_by(Duration::from_nanos(2)), at(0, 1));
+    assert!(at(5, 0) > at(65530, 0));
+    let counter = core::cell::Cell::new(64_000);
+    let mut metronome = Metronome::new(Wrapping16NowSource(&counter), Rate::per_second(30, 1), 10);
+    let mut ticks = 0;
+    let mut frames = 0;
+    // Run for four seconds, wrapping around partway through.
+    for _ in 0 ..= 4000 {
+        for reading in metronome.sample(Mode::TargetFramesPerSecond(Rate::per_second(60, 1))) {
+            match reading {
+                Reading::Tick { .. } => ticks += 1,
+                Reading::Frame { .. } => frames += 1,
+                Reading::Idle { .. } => (),
+                x => unreachable!("unexpected reading {:?}", x),
+            }
+        }
+        counter.set(counter.get().wrapping_add(1));
+    }
+    // Every tick and frame from zero to four seconds, inclusive, with none
+    // lost or doubled at the wrap.
+    assert_eq!(ticks, 4 * 30 + 1);
+    assert_eq!(frames, 4 * 60 + 1);
+}
+#[test]
+fn will_produce_frame() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(5, 1), 10);
+    for (now, mode) in [
+        (Duration::ZERO, Mode::OneFramePerTick),
+        (Duration::ZERO, Mode::OneFramePerTick),
+        (Duration::from_millis(100), Mode::OneFramePerTick),
+        (Duration::from_millis(200), Mode::OneFramePerTick),
+        (Duration::from_millis(300), Mode::TickOnly),
+        (Duration::from_millis(350), Mode::UnlimitedFrames),
+        (Duration::from_millis(350), Mode::UnlimitedFrames),
+    ] {
+        now_source.borrow_mut().now = now;
+        let iterator = metronome.sample(mode);
+        let predicted = iterator.will_produce_frame();
+        let readings: Vec<Reading> = iterator.collect();
+        let actual = readings.iter().any(|x| matches!(x, Reading::Frame{..}));
+        assert_eq!(predicted, actual, "at {:?} in {:?}: {:?}", now, mode, readings);
+    }
+}
+#[test]
+fn tick_boundaries() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 10);
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(60, 1));
+    for n in 0 .. 120u64 {
+        now_source.borrow_mut().now = Duration::from_nanos((n * 1_000_000_000).div_ceil(60));
+        let frames: Vec<bool> = metronome.sample(mode).filter_map(|x| match x {
+            Reading::Frame { on_tick_boundary, .. } => Some(on_tick_boundary),
+            _ => None,
+        }).collect();
+        assert_eq!(frames, &[n % 2 == 0], "frame {}", n);
+    }
+}
+#[test]
+fn max_ticks_behind() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 3);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    // A whole second behind: only three of the ten ticks due are delivered
+    now_source.borrow_mut().now = Duration::from_secs(1);
+    let readings: Vec<Reading> = metronome.sample(Mode::TickOnly).collect();
+    assert_eq!(readings.iter().filter(|x| **x != Reading::TicksLost).count(), 3);
+    assert!(readings.contains(&Reading::TicksLost));
+}
+#[test]
+fn tick_offset() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(3, 1), 10);
+    assert_eq!(metronome.instant_of_tick_offset(0), None);
+    now_source.borrow_mut().now = Duration::from_secs(10);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_secs(11);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.instant_of_tick_offset(0), Some(Duration::from_secs(11)));
+    assert_eq!(metronome.instant_of_tick_offset(-3), Some(Duration::from_secs(10)));
+    assert_eq!(metronome.instant_of_tick_offset(3), Some(Duration::from_secs(12)));
+    assert_eq!(metronome.instant_of_tick_offset(-1), Some(Duration::new(10, 666_666_666)));
+    assert_eq!(metronome.instant_of_tick_offset(1), Some(Duration::new(11, 333_333_333)));
+    assert_eq!(metronome.instant_of_tick_offset(-34), None);
+}
+#[test]
+fn has_started() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    assert!(!metronome.has_started());
+    now_source.borrow_mut().now = Duration::from_secs(1);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert!(metronome.has_started());
+    now_source.borrow_mut().now = Duration::ZERO;
+    assert_eq!(metronome.sample(Mode::TickOnly).next(), Some(Reading::TimeWentBackwards));
+    assert!(!metronome.has_started());
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert!(metronome.has_started());
+}
+#[test]
+fn precise_phase() {
+    // 2.5 nanoseconds per tick. With residuals discarded, the ticks at 5 and
+    // 7.5 nanoseconds look like they're at 5 and 7 nanoseconds.
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(400_000_000, 1), 10);
+    metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_nanos(6);
+    let mut readings = metronome.sample(Mode::UnlimitedFrames);
+    assert_eq!(readings.precise_phase(), None);
+    let mut phases = vec![];
+    while let Some(reading) = readings.next() {
+        if let Reading::Frame { phase, .. } = reading {
+            phases.push((phase, readings.precise_phase().unwrap()));
+        }
+    }
+    assert_eq!(phases.len(), 1);
+    let (phase, precise_phase) = phases[0];
+    assert_eq!(phase, 0.5);
+    assert!((precise_phase - 0.4).abs() < 1e-9, "{}", precise_phase);
+}
+#[test]
+fn anchor_to() {
+    fn ticks(start: u64) -> Vec<Duration> {
+        let now_source = RefCell::new(FakeNowSource::default());
+        now_source.borrow_mut().now = Duration::from_millis(start);
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 5);
+        metronome.anchor_to(Duration::from_secs(1));
+        let mut ticks = vec![];
+        for millis in (start ..= 2000).step_by(7) {
+            now_source.borrow_mut().now = Duration::from_millis(millis);
+            let count = metronome.sample(Mode::UnlimitedFrames)
+                .filter(|x| matches!(x, Reading::Tick { .. })).count() as i32;
+            for offset in 1 - count ..= 0 {
+                ticks.push(metronome.instant_of_tick_offset(offset).unwrap());
+            }
+        }
+        ticks
+    }
+    let early = ticks(500);
+    let late = ticks(1234);
+    let later = ticks(1500);
+    // Joining early, the grid counts backwards from the epoch.
+    assert_eq!(early[0], Duration::from_nanos(500_000_000));
+    assert!(early.contains(&Duration::from_secs(1)));
+    // Joining late, the grid starts at the last tick before the join.
+    assert_eq!(late[0], Duration::from_nanos(1_233_333_333));
+    assert_eq!(later[0], Duration::from_secs(1) + Duration::from_millis(500));
+    assert!(early.ends_with(&late));
+    assert!(late.ends_with(&later));
+}
+#[test]
+fn end_of_time() {
+    let now = core::cell::Cell::new(u64::MAX - 2_500_000);
+    let mut metronome = Metronome::new(BoundedNowSource(&now), Rate::per_second(1000, 1), 5);
+    let readings: Vec<Reading> = metronome.sample(Mode::TickOnly).collect();
+    assert_eq!(readings, [tick(1_000_000)]);
+    now.set(u64::MAX);
+    let readings: Vec<Reading> = metronome.sample(Mode::TickOnly).collect();
+    assert_eq!(readings, [tick(1_000_000), tick(1_000_000), Reading::EndOfTime]);
+    assert_eq!(metronome.instant_of_tick_offset(0), Some(BoundedInstant(u64::MAX - 500_000)));
+    assert_eq!(metronome.instant_of_tick_offset(1), None);
+    let readings: Vec<Reading> = metronome.sample(Mode::UnlimitedFrames).collect();
+    assert_eq!(readings, [Reading::EndOfTime]);
+}
+#[test]
+#[cfg(not(feature="no_std"))]
+fn multiple_outputs() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 5);
+    let preview = metronome.add_output(Rate::per_second(30, 1));
+    assert_eq!(preview, 1);
+    let mut readings = vec![];
+    for millis in 0 ..= 1000 {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        readings.extend(metronome.sample(Mode::TargetFramesPerSecond(Rate::per_second(60, 1))));
+    }
+    let ticks = readings.iter().filter(|x| matches!(x, Reading::Tick { .. })).count();
+    let frames = |id| readings.iter().filter(|x| matches!(x, Reading::Frame { output_id, .. } if *output_id == id)).count();
+    // The ticks are shared, and each output gets its own frames.
+    assert_eq!(ticks, 31);
+    assert_eq!(frames(0), 61);
+    assert_eq!(frames(preview), 31);
+    // Both outputs start on a tick, and 30 FPS stays on the tick boundaries.
+    for reading in readings.iter() {
+        if let Reading::Frame { phase, on_tick_boundary, output_id, .. } = reading {
+            if *output_id == preview {
+                assert_eq!((*phase, *on_tick_boundary), (1.0, true));
+            }
+        }
+    }
+    // The frames come in order, each after the ticks it needs.
+    now_source.borrow_mut().now = Duration::from_nanos(1_050_000_000);
+    let readings: Vec<Reading> = metronome.sample(Mode::TargetFramesPerSecond(Rate::per_second(60, 1))).collect();
+    assert_eq!(readings, [
+        tick(33_333_333),
+        Reading::Frame { phase: 1.0, interpolating: true, on_tick_boundary: true, output_id: 1 },
+        tick(33_333_334),
+        Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
+    ]);
+}
+#[test]
+fn frames_produced() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 2);
+    let mut frames = 0;
+    for (millis, mode) in [
+        (0, Mode::UnlimitedFrames),
+        (50, Mode::TickOnly),
+        (60, Mode::UnlimitedFrames),
+        (170, Mode::UnlimitedFrames),
+        (50, Mode::UnlimitedFrames), // TimeWentBackwards
+        (2000, Mode::UnlimitedFrames), // TicksLost
+        (2010, Mode::OneFramePerTick),
+        (2100, Mode::OneFramePerTick),
+    ] {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        frames += metronome.sample(mode)
+            .filter(|x| matches!(x, Reading::Frame { .. })).count() as u64;
+        assert_eq!(metronome.frames_produced(), frames, "{}", millis);
+    }
+    assert_eq!(frames, 6);
+}
+#[test]
+#[cfg(feature="debug_checks")]
+#[should_panic(expected = "back to 0.3")]
+fn phase_went_backwards() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(1, 1), 5);
+    for millis in [0, 500, 300] {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+    }
+}
+#[test]
+fn grid_error() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 5);
+    assert_eq!(metronome.grid_error(), None);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.grid_error(), Some(GridOffset::Ahead(Duration::ZERO)));
+    // Catch up three ticks, then fall behind a bit.
+    now_source.borrow_mut().now = Duration::from_millis(100);
+    assert_eq!(metronome.sample(Mode::TickOnly).count(), 3);
+    now_source.borrow_mut().now = Duration::from_millis(110);
+    assert_eq!(metronome.grid_error(), Some(GridOffset::Behind(Duration::from_millis(10))));
+    // Interpolating frames puts the grid ahead, by less than a tick.
+    metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+    assert_eq!(metronome.grid_error(), Some(GridOffset::Ahead(Duration::from_nanos(23_333_333))));
+}
+#[test]
+fn snapshot() {
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(60, 1));
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(60000, 1001), 5);
+    for millis in (0 .. 1000).step_by(7) {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        metronome.sample(mode).for_each(drop);
+    }
+    let snapshot = metronome.snapshot();
+    let restored_source = RefCell::new(*now_source.borrow());
+    let mut restored = Metronome::restore(snapshot.clone(), &restored_source);
+    assert_eq!(restored.snapshot(), snapshot);
+    assert_eq!(restored.tick_number(), metronome.tick_number());
+    for millis in (1000 .. 2000).step_by(3) {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        restored_source.borrow_mut().now = Duration::from_millis(millis);
+        let expected: Vec<Reading> = metronome.sample(mode).collect();
+        let actual: Vec<Reading> = restored.sample(mode).collect();
+        assert_eq!(actual, expected, "{}", millis);
+    }
+    #[cfg(feature="serde")]
+    {
+        fn serializable<T: serde::Serialize + serde::de::DeserializeOwned>(_: &T) {}
+        serializable(&snapshot);
+    }
+}
+#[test]
+fn periodic_schedule() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 5);
+    let mut phases = vec![];
+    for millis in 0 ..= 3000 {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        phases.extend(metronome.sample(Mode::TargetFramesPerSecond(Rate::per_second(144, 1)))
+            .filter_map(|x| match x {
+                Reading::Frame { phase, .. } => Some(phase),
+                _ => None,
+            }));
+    }
+    assert_eq!(phases.len(), 433);
+    // 24 frames for every 5 ticks
+    for (n, window) in phases.windows(25).enumerate() {
+        assert_eq!(window[0].to_bits(), window[24].to_bits(), "frame {}", n);
+    }
+    for phase in phases.iter() {
+        assert_eq!((phase * 24.0).fract(), 0.0, "{}", phase);
+    }
+}
+#[test]
+fn first_tick_delayed() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    metronome.set_first_tick_delayed(true);
+    assert_eq!(metronome.would_idle(Mode::TickOnly), Some(Duration::from_millis(100)));
+    let readings: Vec<Reading> = metronome.sample(Mode::TickOnly).collect();
+    assert_eq!(readings, [Reading::Idle { duration: Duration::from_millis(100) }]);
+    assert_eq!(metronome.tick_number(), 0);
+    now_source.borrow_mut().now = Duration::from_millis(99);
+    let readings: Vec<Reading> = metronome.sample(Mode::TickOnly).collect();
+    assert_eq!(readings, [Reading::Idle { duration: Duration::from_millis(1) }]);
+    now_source.borrow_mut().now = Duration::from_millis(100);
+    let readings: Vec<Reading> = metronome.sample(Mode::TickOnly).collect();
+    assert_eq!(readings, [tick(100_000_000)]);
+    assert_eq!(metronome.instant_of_tick_offset(0), Some(Duration::from_millis(100)));
+}
+#[test]
+fn dropped_frames() {
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(60, 1));
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 100);
+    for millis in (0 ..= 500).step_by(5) {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        metronome.sample(mode).for_each(drop);
+    }
+    assert_eq!(metronome.dropped_frames(), 0);
+    // Stall for a tenth of a second: that's five whole frames skipped, and
+    // the sixth rendered.
+    now_source.borrow_mut().now = Duration::from_millis(600);
+    assert!(metronome.sample(mode).any(|x| matches!(x, Reading::Frame { .. })));
+    assert_eq!(metronome.dropped_frames(), 5);
+    now_source.borrow_mut().now = Duration::from_millis(610);
+    metronome.sample(mode).for_each(drop);
+    assert_eq!(metronome.dropped_frames(), 5);
+}
+#[test]
+fn one_frame_per_batch() {
+    for mode in [
+        Mode::OneFramePerTick,
+        Mode::UnlimitedFrames,
+        Mode::TargetFramesPerSecond(Rate::per_second(60, 1)),
+        Mode::TargetFramesPerSecond(Rate::per_second(1000, 1)),
+        Mode::RenderOnly { target_fps: Rate::per_second(60, 1) },
+    ] {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 1000);
+        metronome.set_interpolation_window(Duration::ZERO, Duration::from_secs(10));
+        for millis in (0 ..= 100).step_by(5) {
+            now_source.borrow_mut().now = Duration::from_millis(millis);
+            metronome.sample(mode).for_each(drop);
+        }
+        // A ten second stall, with plenty of room to catch up on ticks
+        now_source.borrow_mut().now = Duration::from_millis(10_100);
+        let frames = metronome.sample(mode)
+            .filter(|x| matches!(x, Reading::Frame { .. }))
+            .count();
+        assert_eq!(frames, 1, "{:?}", mode);
+    }
+}
+#[cfg(not(feature="no_std"))]
+#[test]
+fn clone_with_source() {
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(40, 1));
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 7);
+    metronome.set_label("original");
+    for millis in [0, 20, 45, 90] {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        metronome.sample(mode).for_each(drop);
+    }
+    let times = [110, 130, 175, 200, 1000];
+    let mut replay = metronome.clone_with_source(ScriptedNowSource::new(
+        times.iter().map(|&millis| Duration::from_millis(millis)).collect()
+    ));
+    assert_eq!(replay.label(), Some("original"));
+    assert_eq!(replay.tick_number(), metronome.tick_number());
+    for millis in times {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        assert_eq!(replay.sample(mode).collect::<Vec<_>>(), metronome.sample(mode).collect::<Vec<_>>());
+    }
+    assert_eq!(replay.tick_number(), metronome.tick_number());
+    assert_eq!(replay.frames_produced(), metronome.frames_produced());
+}
+#[test]
+fn report_actual_sleep() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(25, 1), 5);
+    let mut idles = vec![];
+    let mut late = vec![];
+    while idles.len() < 100 {
+        let readings = metronome.sample(Mode::TickOnly).collect::<Vec<_>>();
+        for reading in readings {
+            match reading {
+                Reading::Tick { .. } => late.push(now_source.borrow().now - metronome.instant_of_tick_offset(0).unwrap()),
+                Reading::Idle { duration } => {
+                    idles.push(duration);
+                    // always oversleep by a millisecond
+                    let actual = duration + Duration::from_millis(1);
+                    *now_source.borrow_mut() += actual;
+                    metronome.report_actual_sleep(duration, actual);
+                },
+                _ => (),
+            }
+        }
+    }
+    assert_eq!(idles[0], Duration::from_millis(40));
+    assert_eq!(late[1], Duration::from_millis(1));
+    // Eventually, the idles are short by about the overshoot, and we wake
+    // up about on time.
+    let last_idle = *idles.last().unwrap();
+    assert!(last_idle > Duration::from_micros(38_990) && last_idle < Duration::from_micros(39_010), "{:?}", last_idle);
+    let last_late = *late.last().unwrap();
+    assert!(last_late < Duration::from_micros(10), "{:?}", last_late);
+}
+#[test]
+fn sample_array() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 3);
+    assert_eq!(metronome.sample_array::<4>(Mode::OneFramePerTick), ([
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+        Reading::NoOp,
+        Reading::NoOp,
+    ], 2));
+    now_source.borrow_mut().now = Duration::from_secs(1);
+    let (readings, count) = metronome.sample_array::<{3 + 8}>(Mode::OneFramePerTick);
+    assert_eq!(&readings[.. count], &[
+        tick(100_000_000),
+        tick(100_000_000),
+        tick(100_000_000),
+        Reading::TicksLost,
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: false, output_id: 0 },
+    ]);
+}
+#[test]
+fn sample_array_at_max_batch_len() {
+    // With a slow tick after every tick, a batch is more than twice
+    // `max_ticks_behind` long.
+    const LEN: usize = max_batch_len(10, 1, 0);
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    metronome.set_slow_tick_divisor(1);
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(30, 1));
+    metronome.sample(mode).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_secs(5);
+    metronome.set_tickrate(Rate::per_second(20, 1));
+    let (readings, count) = metronome.sample_array::<LEN>(mode);
+    assert_eq!(count, 23);
+    assert_eq!(readings[0], Reading::TickrateChanged { new: Rate::per_second(20, 1) });
+    assert_eq!(readings[1 .. 21].iter().filter(|x| **x == Reading::SlowTick).count(), 10);
+    assert_eq!(&readings[21 .. count], &[
+        Reading::TicksLost,
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: false, output_id: 0 },
+    ]);
+}
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "more than 1 readings")]
+fn sample_array_overflow() {
+    let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(10, 1), 3);
+    metronome.sample_array::<1>(Mode::OneFramePerTick);
+}
+#[test]
+fn phase_easing() {
+    fn smoothstep(x: f32) -> f32 { x * x * (3.0 - 2.0 * x) }
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(4, 1), 5);
+    metronome.set_phase_easing(Some(smoothstep));
+    let mut phases = vec![];
+    for millis in [0, 63, 125, 250] {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        let mut readings = metronome.sample(Mode::TargetFramesPerSecond(Rate::per_second(16, 1)));
+        while let Some(reading) = readings.next() {
+            if let Reading::Frame { phase, .. } = reading {
+                phases.push((phase, readings.precise_phase().unwrap()));
+            }
+        }
+    }
+    assert_eq!(phases, &[(1.0, 1.0), (0.15625, 0.25), (0.5, 0.5), (1.0, 1.0)]);
+    metronome.set_phase_easing(None);
+    now_source.borrow_mut().now = Duration::from_millis(313);
+    assert!(metronome.sample(Mode::TargetFramesPerSecond(Rate::per_second(16, 1)))
+        .any(|x| x == Reading::Frame { phase: 0.25, interpolating: true, on_tick_boundary: false, output_id: 0 }));
+}
+#[test]
+fn future_frames_skipped() {
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(3, 1));
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 50);
+    for nanos in [0, 333_333_334, 666_666_667] {
+        now_source.borrow_mut().now = Duration::from_nanos(nanos);
+        assert!(metronome.sample(mode).any(|x| matches!(x, Reading::Frame { .. })));
+    }
+    assert_eq!(metronome.future_frames_skipped(), 0);
+    // The third frame is at exactly one second, a nanosecond from now.
+    now_source.borrow_mut().now = Duration::from_nanos(999_999_999);
+    assert!(!metronome.sample(mode).any(|x| matches!(x, Reading::Frame { .. })));
+    assert_eq!(metronome.future_frames_skipped(), 1);
+    now_source.borrow_mut().now = Duration::from_secs(1);
+    assert!(metronome.sample(mode).any(|x| matches!(x, Reading::Frame { .. })));
+    assert_eq!(metronome.future_frames_skipped(), 1);
+}
+#[test]
+fn tick_at_instant() {
+    let now_source = RefCell::new(FakeNowSource { now: Duration::from_secs(1) });
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(3, 1), 5);
+    assert_eq!(metronome.tick_at_instant(Duration::from_secs(1)), None);
+    // Tick 1 at 1 second, tick 2 at 1⅓ seconds, tick 3 at 1⅔ seconds...
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_millis(1700);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.tick_number(), 3);
+    for (nanos, tick) in [
+        (999_999_999, None),
+        (1_000_000_000, Some(1)),
+        (1_333_333_332, Some(1)),
+        (1_333_333_333, Some(2)),
+        (1_666_666_666, Some(3)),
+        (1_700_000_000, Some(3)),
+        (1_999_999_999, Some(3)),
+        (2_000_000_000, Some(4)),
+        (11_000_000_000, Some(31)),
+    ] {
+        assert_eq!(metronome.tick_at_instant(Duration::from_nanos(nanos)), tick, "{}", nanos);
+    }
+    // The grid starts over after losing ticks, but keeps counting.
+    now_source.borrow_mut().now = Duration::from_millis(60_500);
+    assert_eq!(metronome.sample(Mode::TickOnly).last(), Some(Reading::TicksLost));
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    let tick_number = metronome.tick_number();
+    assert_eq!(metronome.tick_at_instant(Duration::from_millis(60_500)), Some(tick_number));
+    assert_eq!(metronome.tick_at_instant(Duration::from_millis(60_800)), Some(tick_number));
+    assert_eq!(metronome.tick_at_instant(Duration::from_millis(60_900)), Some(tick_number + 1));
+}
+#[cfg(not(feature="no_std"))]
+#[test]
+fn tick_history() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.tick_history(), &[]);
+    metronome.set_history_depth(3);
+    now_source.borrow_mut().now = Duration::from_millis(200);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.tick_history(), &[Duration::from_millis(100), Duration::from_millis(200)]);
+    now_source.borrow_mut().now = Duration::from_millis(500);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.tick_history(), &[
+        Duration::from_millis(300), Duration::from_millis(400), Duration::from_millis(500),
+    ]);
+    metronome.run_ticks(1, || ());
+    assert_eq!(metronome.tick_history(), &[
+        Duration::from_millis(400), Duration::from_millis(500), Duration::from_millis(600),
+    ]);
+    // and so on, for many more ticks than the depth
+    for n in 7 ..= 20 {
+        metronome.run_ticks(1, || ());
+        let expected: Vec<Duration> = (n - 2 ..= n).map(|x| Duration::from_millis(x * 100)).collect();
+        assert_eq!(metronome.tick_history(), &expected[..]);
+    }
+    metronome.set_history_depth(2);
+    assert_eq!(metronome.tick_history(), &[Duration::from_millis(1900), Duration::from_millis(2000)]);
+    metronome.set_history_depth(0);
+    assert_eq!(metronome.tick_history(), &[]);
+}
+#[cfg(feature="async")]
+#[test]
+fn sample_async() {
+    use core::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(x) = future.as_mut().poll(&mut cx) { return x }
+        }
+    }
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    let slept = RefCell::new(vec![]);
+    // Time passes only while we're asleep.
+    let sleeper = |duration| {
+        slept.borrow_mut().push(duration);
+        let now_source = &now_source;
+        async move { *now_source.borrow_mut() += duration }
+    };
+    assert_eq!(block_on(async {
+        metronome.sample_async(Mode::TickOnly, sleeper).await.collect::<Vec<_>>()
+    }), &[tick(100_000_000)]);
+    assert_eq!(slept.borrow().len(), 0);
+    assert_eq!(block_on(async {
+        metronome.sample_async(Mode::TickOnly, sleeper).await.collect::<Vec<_>>()
+    }), &[tick(100_000_000)]);
+    assert_eq!(*slept.borrow(), &[Duration::from_millis(100)]);
+    assert_eq!(now_source.borrow().now, Duration::from_millis(100));
+}
+#[test]
+fn first_tick() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    metronome.set_announce_first_tick(true);
+    let run_until = |metronome: &mut Metronome<_>, millis| {
+        let mut readings = vec![];
+        while now_source.borrow().now < Duration::from_millis(millis) {
+            readings.extend(metronome.sample(Mode::OneFramePerTick));
+            *now_source.borrow_mut() += Duration::from_millis(25);
+        }
+        readings
+    };
+    let readings = run_until(&mut metronome, 1000);
+    assert_eq!(readings.iter().filter(|x| **x == Reading::FirstTick).count(), 1);
+    assert_eq!(&readings[.. 2], &[Reading::FirstTick, tick(100_000_000)]);
+    metronome.reset();
+    assert_eq!(metronome.tick_number(), 0);
+    let readings = run_until(&mut metronome, 2000);
+    assert_eq!(&readings[.. 2], &[Reading::FirstTick, tick(100_000_000)]);
+    assert_eq!(readings.iter().filter(|x| **x == Reading::FirstTick).count(), 1);
+    assert_eq!(metronome.tick_number(), 10);
+    // Going backwards starts over, too.
+    now_source.borrow_mut().now = Duration::from_millis(500);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        Reading::TimeWentBackwards,
+        Reading::FirstTick,
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    // Without announcements, not a peep.
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    assert!(!metronome.sample(Mode::OneFramePerTick).any(|x| x == Reading::FirstTick));
+}
+#[test]
+fn time_until_next_frame() {
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(60, 1));
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(7, 1), 5);
+    assert_eq!(metronome.time_until_next_frame(mode), Some(Duration::ZERO));
+    metronome.sample(mode).for_each(drop);
+    assert_eq!(metronome.time_until_next_frame(mode), Some(Duration::from_nanos(16_666_666)));
+    now_source.borrow_mut().now = Duration::from_millis(10);
+    assert_eq!(metronome.time_until_next_frame(mode), Some(Duration::from_nanos(6_666_666)));
+    // It's the frame's deadline, not the tick's.
+    now_source.borrow_mut().now = Duration::from_millis(140);
+    metronome.sample(mode).for_each(drop);
+    assert_eq!(metronome.time_until_next_frame(mode), Some(Duration::from_nanos(10_000_000)));
+    assert_eq!(metronome.time_until_next_frame(Mode::UnlimitedFrames), None);
+    assert_eq!(metronome.time_until_next_frame(Mode::TickOnly), None);
+}
+#[test]
+fn framerate_equals_tickrate() {
+    let rate = Rate::per_second(30, 1);
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, rate, 5);
+    let mut frames = 0;
+    for nanos in (0 .. 5_000_000_000).step_by(1_234_567) {
+        now_source.borrow_mut().now = Duration::from_nanos(nanos);
+        for reading in metronome.sample(Mode::TargetFramesPerSecond(rate)) {
+            if let Reading::Frame { phase, on_tick_boundary, .. } = reading {
+                assert_eq!((phase, on_tick_boundary), (1.0, true), "{}", nanos);
+                frames += 1;
+            }
+        }
+    }
+    assert_eq!(frames, 150);
+    // Even if the frame grid was laid down for a different tickrate.
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(7, 1), 5);
+    let mut frames = 0;
+    for (n, nanos) in (0 .. 5_000_000_000).step_by(1_234_567).enumerate() {
+        if n == 100 {
+            metronome.set_tickrate(rate);
+        }
+        now_source.borrow_mut().now = Duration::from_nanos(nanos);
+        for reading in metronome.sample(Mode::TargetFramesPerSecond(rate)) {
+            if let Reading::Frame { phase, on_tick_boundary, .. } = reading {
+                if n > 100 {
+                    assert_eq!((phase, on_tick_boundary), (1.0, true), "{}", nanos);
+                    frames += 1;
+                }
+            }
+        }
+    }
+    assert_eq!(frames, 146);
+}
+#[test]
+fn idle_fraction() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 5);
+    assert_eq!(metronome.idle_fraction(), None);
+    while now_source.borrow().now < Duration::from_secs(3) {
+        let mut idle = Duration::ZERO;
+        for reading in metronome.sample(Mode::OneFramePerTick) {
+            match reading {
+                // a millisecond of work per tick and per frame
+                Reading::Tick { .. } | Reading::Frame { .. } => *now_source.borrow_mut() += Duration::from_millis(1),
+                Reading::Idle { duration } => idle = duration,
+                _ => (),
+            }
+        }
+        *now_source.borrow_mut() += idle;
+    }
+    // two milliseconds out of every 33 and a third
+    let fraction = metronome.idle_fraction().unwrap();
+    assert!((fraction - 0.94).abs() < 0.01, "{}", fraction);
+}
+#[test]
+fn merge_ticks() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut gameplay = Metronome::new(&now_source, Rate::per_second(30, 1), 10);
+    let mut dsp = Metronome::new(&now_source, Rate::per_second(45, 1), 10);
+    let mut ticks = vec![];
+    for _ in 0 ..= 10 {
+        for (which, reading) in super::merge_ticks(&mut gameplay, &mut dsp, Mode::TickOnly) {
+            if let Reading::Tick { .. } = reading { ticks.push(which) }
+        }
+        *now_source.borrow_mut() += Duration::from_millis(100);
+    }
+    // a tick at k/30 comes before one at j/45 if 3k <= 2j
+    let mut expected = vec![];
+    let (mut k, mut j) = (0, 0);
+    while k <= 30 || j <= 45 {
+        if j > 45 || (k <= 30 && 3 * k <= 2 * j) {
+            expected.push(Which::A);
+            k += 1;
+        } else {
+            expected.push(Which::B);
+            j += 1;
+        }
+    }
+    assert_eq!(ticks, expected);
+}
+#[test]
+fn label() {
+    let mut metronome = Metronome::new(FakeNowSource::default(), Rate::per_second(30, 1), 5);
+    assert_eq!(metronome.label(), None);
+    metronome.set_label("simulation");
+    assert_eq!(metronome.label(), Some("simulation"));
+    assert!(format!("{:?}", metronome).contains("\"simulation\""));
+}
+#[test]
+fn fixed_phase() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(3, 1), 10);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    for millis in [0, 1, 37, 100, 250, 333, 334] {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        let mut readings = metronome.sample(Mode::UnlimitedFrames);
+        assert_eq!(readings.fixed_phase(), None);
+        let mut phases = vec![];
+        while let Some(reading) = readings.next() {
+            if let Reading::Frame { phase, .. } = reading {
+                phases.push((phase, readings.fixed_phase().unwrap()));
+            }
+        }
+        assert_eq!(phases.len(), 1);
+        let (phase, fixed_phase) = phases[0];
+        assert!(fixed_phase <= 65536, "{:?}", millis);
+        let difference = phase - fixed_phase as f32 / 65536.0;
+        assert!((0.0 ..= 1.0 / 65536.0).contains(&difference), "{:?}", millis);
+    }
+    // 100000000 * 65536 / 333333333, rounded down
+    now_source.borrow_mut().now = Duration::from_millis(400);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_nanos(433_333_333);
+    let mut readings = metronome.sample(Mode::UnlimitedFrames);
+    readings.by_ref().for_each(drop);
+    assert_eq!(readings.fixed_phase(), Some(19660));
+}
+#[test]
+fn deterministic_phase() {
+    fn phase(deterministic: bool, tps: u32, now: Duration) -> f32 {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(tps, 1), 10);
+        metronome.set_deterministic_phase(deterministic);
+        metronome.sample(Mode::TickOnly).for_each(drop);
+        now_source.borrow_mut().now = now;
+        metronome.sample(Mode::UnlimitedFrames).find_map(|x| match x {
+            Reading::Frame { phase, .. } => Some(phase),
+            _ => None,
+        }).unwrap()
+    }
+    // 100000000 / 333333333, rounded to the nearest 2^-24
+    assert_eq!(phase(true, 3, Duration::from_millis(100)).to_bits(), 0x3e99999a);
+    assert_eq!(phase(true, 3, Duration::from_nanos(333_333_333)).to_bits(), 1.0f32.to_bits());
+    for millis in [1, 37, 250, 333] {
+        let now = Duration::from_millis(millis);
+        let difference = phase(true, 3, now) - phase(false, 3, now);
+        assert!(difference.abs() <= 1.0 / 16777216.0, "{:?}", now);
+    }
+}
+#[test]
+fn frame_lead() {
+    fn readings(lead: Duration, now: Duration) -> Vec<Reading> {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 10);
+        metronome.set_frame_lead(lead);
+        let mode = Mode::TargetFramesPerSecond(Rate::per_second(60, 1));
+        metronome.sample(mode).for_each(drop);
+        now_source.borrow_mut().now = now;
+        metronome.sample(mode).collect()
+    }
+    // The second frame is at 16.67ms. Without a lead, it comes at 16.67ms...
+    let lead = Duration::from_millis(3);
+    assert_eq!(readings(Duration::ZERO, Duration::from_micros(13_700)), &[
+        Reading::Idle { duration: Duration::from_nanos(2_966_666) },
+    ]);
+    assert_eq!(readings(Duration::ZERO, Duration::from_nanos(16_666_666)), &[
+        tick(33_333_333),
+        Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
+    ]);
+    // ...and with a 3ms lead, it comes at 13.67ms.
+    assert_eq!(readings(lead, Duration::from_micros(13_600)), &[
+        Reading::Idle { duration: Duration::from_nanos(66_666) },
+    ]);
+    assert_eq!(readings(lead, Duration::from_micros(13_700)), &[
+        tick(33_333_333),
+        Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
+    ]);
+}
+#[test]
+fn switch_to_target_fps() {
+    // Switching into `TargetFramesPerSecond` while the next tick is still
+    // in the future used to panic.
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_millis(50);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(60, 1));
+    metronome.sample(mode).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_millis(100);
+    assert!(metronome.sample(mode).any(|x| matches!(x, Reading::Frame { .. })));
+}
+#[test]
+fn frame_bracket() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(4, 1), 10);
+    assert_eq!(metronome.frame_bracket(), None);
+    metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+    // only one tick so far
+    assert_eq!(metronome.frame_bracket(), None);
+    now_source.borrow_mut().now = Duration::from_millis(100);
+    metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+    assert_eq!(metronome.frame_bracket(), Some((Duration::ZERO, Duration::from_millis(250))));
+    now_source.borrow_mut().now = Duration::from_millis(600);
+    metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+    assert_eq!(metronome.frame_bracket(), Some((Duration::from_millis(500), Duration::from_millis(750))));
+    // Ticking without a frame doesn't move it.
+    now_source.borrow_mut().now = Duration::from_millis(800);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.frame_bracket(), Some((Duration::from_millis(500), Duration::from_millis(750))));
+}
+#[test]
+fn adaptive() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 10);
+    // Roughly 60Hz, with a little jitter and the odd hiccup.
+    let mut now = Duration::ZERO;
+    for n in 0 .. 70u64 {
+        now_source.borrow_mut().now = now;
+        let readings: Vec<Reading> = metronome.sample(Mode::Adaptive).collect();
+        if metronome.adaptive_framerate().is_none() {
+            // still acting like `UnlimitedFrames`
+            assert!(readings.iter().any(|x| matches!(x, Reading::Frame { .. })), "{:?}", readings);
+            assert!(!readings.iter().any(|x| matches!(x, Reading::Idle { .. })), "{:?}", readings);
+            assert!(now < Duration::from_secs(1));
+        }
+        now += match n % 10 {
+            3 => Duration::from_micros(16_900),
+            7 => Duration::from_micros(16_400),
+            9 => Duration::from_millis(40),
+            _ => Duration::from_nanos(16_666_667),
+        };
+    }
+    assert_eq!(metronome.adaptive_framerate(), Some(Rate::per_second(60, 1)));
+    // From now on, it acts like `TargetFramesPerSecond`: sampling every
+    // millisecond for a tenth of a second gives six frames, not a hundred.
+    let mut frames = 0;
+    for _ in 0 .. 100 {
+        now_source.borrow_mut().now += Duration::from_millis(1);
+        frames += metronome.sample(Mode::Adaptive)
+            .filter(|x| matches!(x, Reading::Frame { .. })).count();
+    }
+    assert_eq!(frames, 6);
+}
+#[test]
+fn pace_frame() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(20, 1), 10);
+    let mut readings = vec![];
+    let mut slept = vec![];
+    let mut returned = vec![];
+    for _ in 0 .. 3 {
+        returned.push(metronome.pace_frame_with(Mode::OneFramePerTick, |x| readings.push(x), |duration| {
+            slept.push(duration);
+            now_source.borrow_mut().now += duration;
+        }));
+    }
+    assert_eq!(returned, &[None, Some(Duration::from_millis(50)), None]);
+    assert_eq!(readings, &[
+        tick(50_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+        tick(50_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    assert_eq!(slept, &[Duration::from_millis(50)]);
+}
+#[test]
+fn max_idle() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(5, 1), 10);
+    metronome.set_max_idle(Duration::from_millis(16));
+    metronome.sample(Mode::OneFramePerTick).for_each(drop);
+    let mut idles = vec![];
+    loop {
+        let readings: Vec<Reading> = metronome.sample(Mode::OneFramePerTick).collect();
+        match readings[..] {
+            [Reading::Idle { duration }] => {
+                idles.push(duration);
+                now_source.borrow_mut().now += duration;
+            },
+            _ => {
+                assert_eq!(readings[0], tick(200_000_000));
+                break
+            },
+        }
+    }
+    let mut expected = vec![Duration::from_millis(16); 12];
+    expected.push(Duration::from_millis(8));
+    assert_eq!(idles, expected);
+    assert_eq!(now_source.borrow().now, Duration::from_millis(200));
+}
+#[test]
+fn would_idle() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    assert_eq!(metronome.would_idle(Mode::OneFramePerTick), None);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    now_source.borrow_mut().now = Duration::from_millis(30);
+    assert_eq!(metronome.would_idle(Mode::OneFramePerTick), Some(Duration::from_millis(70)));
+    // Asking didn't change anything.
+    assert_eq!(metronome.would_idle(Mode::OneFramePerTick), Some(Duration::from_millis(70)));
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        Reading::Idle { duration: Duration::from_millis(70) },
+    ]);
+    // Switching modes would produce a frame.
+    assert_eq!(metronome.would_idle(Mode::UnlimitedFrames), None);
+    now_source.borrow_mut().now = Duration::from_millis(100);
+    assert_eq!(metronome.would_idle(Mode::OneFramePerTick), None);
+}
+#[test]
+fn idle_range() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    let margin = Duration::from_millis(2);
+    assert_eq!(metronome.idle_range(Mode::OneFramePerTick, margin), None);
+    metronome.sample(Mode::OneFramePerTick).for_each(drop);
+    for millis in [30, 99] {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        let range = metronome.idle_range(Mode::OneFramePerTick, margin).unwrap();
+        let exact = Duration::from_millis(100 - millis);
+        assert!(range.contains(&exact));
+        assert_eq!(*range.start(), exact.saturating_sub(margin));
+        assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+            Reading::Idle { duration: *range.end() },
+        ]);
+    }
+}
+#[test]
+fn next_wake_instant() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(3, 1), 10);
+    assert_eq!(metronome.next_wake_instant(Mode::OneFramePerTick), None);
+    metronome.sample(Mode::OneFramePerTick).for_each(drop);
+    for millis in [0, 100, 333] {
+        let now = Duration::from_millis(millis);
+        now_source.borrow_mut().now = now;
+        let wake = metronome.next_wake_instant(Mode::OneFramePerTick).unwrap();
+        assert_eq!(wake, Duration::from_nanos(333_333_333));
+        assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+            Reading::Idle { duration: wake - now },
+        ]);
+    }
+    now_source.borrow_mut().now = Duration::from_nanos(333_333_333);
+    assert_eq!(metronome.next_wake_instant(Mode::OneFramePerTick), None);
+}
+#[test]
+fn elapsed_ticks() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 3);
+    assert_eq!(metronome.tick_number(), 0);
+    assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+    ]);
+    let start = metronome.tick_number();
+    assert_eq!(start, 1);
+    now_source.borrow_mut().now = Duration::from_secs(1);
+    assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        tick(100_000_000),
+        tick(100_000_000),
+        Reading::TicksLost,
+    ]);
+    // three ticks delivered, six more (at 0.4 through 0.9 seconds) lost
+    assert_eq!(metronome.elapsed_ticks_since(start), 9);
+    assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+    ]);
+    // eleven ticks, from 0.0 through 1.0 seconds inclusive
+    assert_eq!(metronome.tick_number(), 11);
+    assert_eq!(metronome.elapsed_ticks_since(start), 10);
+    assert_eq!(metronome.elapsed_ticks_since(9999), 0);
+}
+#[test]
+fn render_only() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    let mode = Mode::RenderOnly { target_fps: Rate::per_second(40, 1) };
+    metronome.set_interpolation_window(Duration::ZERO, Duration::from_millis(100));
+    for (n, phase) in [0.0, 0.25, 0.5, 0.75, 1.0, 1.0].into_iter().enumerate() {
+        now_source.borrow_mut().now = Duration::from_millis(n as u64 * 25);
+        assert_eq!(metronome.sample(mode).collect::<Vec<_>>(), &[
+            Reading::Frame { phase, interpolating: true, on_tick_boundary: n == 4, output_id: 0 },
+        ]);
+        assert_eq!(metronome.sample(mode).collect::<Vec<_>>(), &[
+            Reading::Idle { duration: Duration::from_millis(25) },
+        ]);
+    }
+    assert_eq!(metronome.tick_number(), 0);
+}
+#[test]
+fn long_suspend() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(1, 3600), 5);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        tick(3_600_000_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    // about thirty thousand years later...
+    now_source.borrow_mut().now = Duration::from_secs(1_000_000_000_000);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        tick(3_600_000_000_000),
+        tick(3_600_000_000_000),
+        tick(3_600_000_000_000),
+        tick(3_600_000_000_000),
+        tick(3_600_000_000_000),
+        Reading::TicksLost,
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: false, output_id: 0 },
+    ]);
+}
+#[test]
+fn skip_frame_on_ticks_lost() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 3);
+    metronome.set_skip_frame_on_ticks_lost(true);
+    assert_eq!(metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    now_source.borrow_mut().now = Duration::from_secs(1);
+    assert_eq!(metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        tick(100_000_000),
+        tick(100_000_000),
+        Reading::TicksLost,
+    ]);
+    assert_eq!(metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+}
+#[test]
+fn never_empty() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 3);
+    metronome.set_never_empty(true);
+    assert_eq!(metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    let readings = metronome.sample(Mode::UnlimitedFrames);
+    assert_eq!(readings.size_hint(), (0, Some(1)));
+    assert_eq!(readings.collect::<Vec<_>>(), &[Reading::NoOp]);
+    now_source.borrow_mut().now = Duration::from_millis(50);
+    assert_eq!(metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
+    ]);
+    metronome.set_never_empty(false);
+    assert_eq!(metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>(), &[]);
+}
+#[test]
+fn catch_up_policy() {
+    fn stall(policy: CatchUpPolicy) -> (Duration, Vec<Reading>) {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 3);
+        metronome.set_catch_up_policy(policy);
+        metronome.sample(Mode::TickOnly).for_each(drop);
+        now_source.borrow_mut().now = Duration::from_millis(1050);
+        assert_eq!(metronome.sample(Mode::TickOnly).last(), Some(Reading::TicksLost));
+        assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+            tick(100_000_000),
+        ]);
+        let restarted_at = metronome.instant_of_tick_offset(0).unwrap();
+        now_source.borrow_mut().now = Duration::from_millis(1120);
+        (restarted_at, metronome.sample(Mode::TickOnly).collect())
+    }
+    assert_eq!(stall(CatchUpPolicy::JumpToNow), (Duration::from_millis(1050), vec![
+        Reading::Idle { duration: Duration::from_millis(30) },
+    ]));
+    assert_eq!(stall(CatchUpPolicy::StayAnchored), (Duration::from_millis(1000), vec![
+        tick(100_000_000),
+    ]));
+}
+#[cfg(not(feature="no_std"))]
+#[test]
+fn simulate_frame_schedule() {
+    let phases = Metronome::simulate_frame_schedule(Rate::per_second(30, 1), Rate::per_second(60, 1), Duration::from_secs(1));
+    assert_eq!(phases.len(), 60);
+    assert_eq!(phases[0], 1.0);
+    for pair in phases[1 ..].chunks(2) {
+        assert_eq!(pair, &[0.5, 1.0][.. pair.len()]);
+    }
+    // 144 fps against 30 TPS repeats every 24 frames, and every frame is
+    // produced exactly on time.
+    let phases = Metronome::simulate_frame_schedule(Rate::per_second(30, 1), Rate::per_second(144, 1), Duration::from_secs(1));
+    assert_eq!(phases.len(), 144);
+    assert_eq!(phases[1 .. 25], phases[25 .. 49]);
+}
+#[test]
+fn phase_jitter() {
+    fn jitter(frame_times: &[u64]) -> Option<f32> {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(4, 1), 10);
+        for &millis in frame_times {
+            now_source.borrow_mut().now = Duration::from_millis(millis);
+            metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+        }
+        metronome.phase_jitter()
+    }
+    assert_eq!(jitter(&[0]), None);
+    assert_eq!(jitter(&[0, 125, 250, 375, 500, 625]), Some(0.0));
+    let irregular = jitter(&[0, 100, 125, 300, 500]).unwrap();
+    assert!(irregular > 0.5, "{}", irregular);
+}
+#[test]
+fn last_now() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 10);
+    assert_eq!(metronome.last_now(), None);
+    now_source.borrow_mut().now = Duration::new(12, 345);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    assert_eq!(metronome.last_now(), Some(Duration::new(12, 345)));
+    // Only updated when the clock is actually read.
+    now_source.borrow_mut().now = Duration::new(13, 0);
+    assert_eq!(metronome.last_now(), Some(Duration::new(12, 345)));
+}
+#[test]
+fn prime() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    now_source.borrow_mut().now = Duration::from_secs(5);
+    metronome.prime();
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        Reading::Idle { duration: Duration::from_millis(100) },
+    ]);
+    assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+        Reading::Idle { duration: Duration::from_millis(100) },
+    ]);
+    now_source.borrow_mut().now = Duration::from_millis(5100);
+    assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+}
+#[test]
+fn prime_anchored() {
+    // Priming shouldn't knock the grid off the anchor.
+    fn ticks(prime_at: Option<u64>) -> Vec<Duration> {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 5);
+        metronome.anchor_to(Duration::from_secs(1));
+        if let Some(millis) = prime_at {
+            now_source.borrow_mut().now = Duration::from_millis(millis);
+            metronome.prime();
+        }
+        let mut ticks = vec![];
+        for millis in (prime_at.unwrap_or(1000) ..= 2000).step_by(7) {
+            now_source.borrow_mut().now = Duration::from_millis(millis);
+            let count = metronome.sample(Mode::UnlimitedFrames)
+                .filter(|x| matches!(x, Reading::Tick { .. })).count() as i32;
+            for offset in 1 - count ..= 0 {
+                ticks.push(metronome.instant_of_tick_offset(offset).unwrap());
+            }
+        }
+        ticks
+    }
+    let unprimed = ticks(None);
+    let primed = ticks(Some(1234));
+    assert_eq!(primed[0], Duration::from_nanos(1_266_666_666));
+    assert!(unprimed.ends_with(&primed));
+}
+#[test]
+fn phase_snap() {
+    // Just a hair faster than the tickrate, so the second frame lands a
+    // millionth of a tick before the second tick.
+    let mode = Mode::TargetFramesPerSecond(Rate::per_second(60_000_060, 1_000_000));
+    let second_frame = Duration::from_nanos(16_666_650);
+    for (epsilon, expected) in [(0.0, 0.99999905), (0.00001, 1.0)] {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(60, 1), 10);
+        metronome.set_phase_snap(epsilon);
+        metronome.sample(mode).for_each(drop);
+        now_source.borrow_mut().now = second_frame;
+        assert_eq!(metronome.sample(mode).collect::<Vec<_>>(), &[
+            tick(16_666_667),
+            Reading::Frame { phase: expected, interpolating: true, on_tick_boundary: false, output_id: 0 },
+        ], "epsilon = {}", epsilon);
+    }
+}
+#[test]
+fn tick_dt_follows_tickrate() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(4, 1), 10);
+    assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+        tick(250_000_000),
+    ]);
+    metronome.set_tickrate(Rate::per_second(10, 1));
+    now_source.borrow_mut().now = Duration::from_millis(450);
+    assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+        Reading::TickrateChanged { new: Rate::per_second(10, 1) },
+        tick(100_000_000),
+        tick(100_000_000),
+        tick(100_000_000),
+        tick(100_000_000),
+    ]);
+}
+#[cfg(not(feature="no_std"))]
+#[test]
+fn scripted() {
+    let now_source = ScriptedNowSource::new(vec![
+        Duration::ZERO,
+        Duration::from_millis(50),
+        Duration::from_millis(300),
+    ]);
+    let mut metronome = Metronome::new(now_source, Rate::per_second(10, 1), 10);
+    let mut readings = vec![];
+    for _ in 0 .. 4 {
+        readings.extend(metronome.sample(Mode::OneFramePerTick));
+    }
+    assert_eq!(readings, &[
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+        Reading::Idle { duration: Duration::from_millis(50) },
+        tick(100_000_000),
+        tick(100_000_000),
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+        // the script has run out, so time stands still
+        Reading::Idle { duration: Duration::from_millis(100) },
+    ]);
+}
+#[test]
+fn pause() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    let sample_at = |metronome: &mut Metronome<_>, millis| {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>()
+    };
+    let frame = |phase| Reading::Frame { phase, interpolating: true, on_tick_boundary: false, output_id: 0 };
+    sample_at(&mut metronome, 0);
+    assert_eq!(sample_at(&mut metronome, 40), &[
+        Reading::Tick { dt: Duration::from_millis(100) },
+        frame(0.4),
+    ]);
+    metronome.pause();
+    assert!(metronome.is_paused());
+    for millis in [70, 140, 250] {
+        assert_eq!(sample_at(&mut metronome, millis), &[frame(0.4)]);
+    }
+    now_source.borrow_mut().now = Duration::from_millis(300);
+    metronome.resume();
+    assert!(!metronome.is_paused());
+    // The pause lasted 260ms, so the tick that was due at 100ms is now due
+    // at 360ms, and the one after it at 460ms.
+    assert_eq!(sample_at(&mut metronome, 300), &[frame(0.4)]);
+    assert_eq!(sample_at(&mut metronome, 330), &[frame(0.7)]);
+    assert_eq!(sample_at(&mut metronome, 360), &[
+        Reading::Frame { phase: 1.0, interpolating: true, on_tick_boundary: true, output_id: 0 },
+    ]);
+    assert_eq!(sample_at(&mut metronome, 370), &[
+        Reading::Tick { dt: Duration::from_millis(100) },
+        frame(0.1),
+    ]);
+}
+#[test]
+fn render_delay() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    metronome.set_render_delay(Duration::from_millis(100));
+    let mut phases = vec![];
+    for millis in [1000, 1040, 1140, 1190, 1200] {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        let readings = metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>();
+        for reading in readings {
+            if let Reading::Frame { phase, on_tick_boundary, .. } = reading {
+                let bracket = metronome.frame_bracket()
+                    .map(|(past, future)| (past.as_millis(), future.as_millis()));
+                phases.push((phase, on_tick_boundary, bracket));
+            }
+        }
+    }
+    // Ticks are still delivered in real time, but each frame is interpolated
+    // a tick behind: between the two ticks before the current one.
+    assert_eq!(metronome.tick_number(), 3);
+    assert_eq!(phases, &[
+        (1.0, true, None),
+        (0.4, false, Some((900, 1000))),
+        (0.4, false, Some((1000, 1100))),
+        (0.9, false, Some((1000, 1100))),
+        (1.0, true, Some((1000, 1100))),
+    ]);
+}
+#[test]
+fn clock_stalled() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 5);
+    metronome.set_clock_stall_threshold(3);
+    let mut stalls = vec![];
+    for nanos in [0, 0, 0, 0, 0, 0, 1_000_000, 1_000_000, 1_000_000, 1_000_000] {
+        now_source.borrow_mut().now = Duration::from_nanos(nanos);
+        stalls.push(metronome.sample(Mode::TickOnly).any(|x| x == Reading::ClockStalled));
+    }
+    assert_eq!(stalls, &[false, false, false, true, false, false, false, false, false, true]);
+    // Other calls that read the clock don't count as samples
+    metronome.set_clock_stall_threshold(1);
+    now_source.borrow_mut().now = Duration::from_millis(100);
+    assert_eq!(metronome.would_idle(Mode::TickOnly), None);
+    assert!(!metronome.sample(Mode::TickOnly).any(|x| x == Reading::ClockStalled));
+    assert!(metronome.sample(Mode::TickOnly).any(|x| x == Reading::ClockStalled));
+}
+#[test]
+fn nearest_tick_instant() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    let millis = Duration::from_millis;
+    assert_eq!(metronome.nearest_tick_instant(millis(0)), None);
+    for at in [1000, 1100, 1200] {
+        now_source.borrow_mut().now = millis(at);
+        metronome.sample(Mode::TickOnly).for_each(drop);
+    }
+    assert_eq!(metronome.nearest_tick_instant(millis(1130)), Some(millis(1100)));
+    assert_eq!(metronome.nearest_tick_instant(millis(1170)), Some(millis(1200)));
+    assert_eq!(metronome.nearest_tick_instant(millis(1200)), Some(millis(1200)));
+    // extrapolated both ways, and halfway goes to the earlier tick
+    assert_eq!(metronome.nearest_tick_instant(millis(1350)), Some(millis(1300)));
+    assert_eq!(metronome.nearest_tick_instant(millis(951)), Some(millis(1000)));
+    assert_eq!(metronome.nearest_tick_instant(millis(949)), Some(millis(900)));
+}
+#[test]
+fn boundary_phase() {
+    for (boundary, on_tick) in [(BoundaryPhase::EndOfInterval, 1.0), (BoundaryPhase::StartOfInterval, 0.0)] {
+        for mode in [Mode::UnlimitedFrames, Mode::TargetFramesPerSecond(Rate::per_second(20, 1))] {
+            let now_source = RefCell::new(FakeNowSource::default());
+            let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+            metronome.set_boundary_phase(boundary);
+            let mut frames = vec![];
+            for millis in [0, 50, 100, 150, 200] {
+                now_source.borrow_mut().now = Duration::from_millis(millis);
+                let readings = metronome.sample(mode).collect::<Vec<_>>();
+                for reading in readings {
+                    if let Reading::Frame { phase, on_tick_boundary, .. } = reading {
+                        frames.push((phase, on_tick_boundary, metronome.tick_number()));
+                    }
+                }
+            }
+            // On the boundary, the frame is either the end of one interval
+            // or, a tick later, the start of the next.
+            let ahead = (on_tick == 0.0) as u64;
+            assert_eq!(frames, &[
+                (on_tick, true, 1 + ahead),
+                (0.5, false, 2),
+                (on_tick, true, 2 + ahead),
+                (0.5, false, 3),
+                (on_tick, true, 3 + ahead),
+            ], "{:?} {:?}", boundary, mode);
+        }
+    }
+}
+#[test]
+fn catch_up_style() {
+    let mut batches = vec![];
+    for style in [CatchUpStyle::Uniform, CatchUpStyle::SingleBigStep] {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+        metronome.set_catch_up_style(style);
+        let mut dts = vec![];
+        for millis in [0, 550, 600] {
+            now_source.borrow_mut().now = Duration::from_millis(millis);
+            let readings = metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>();
+            assert!(matches!(readings.last(), Some(Reading::Frame { phase: 1.0, .. })));
+            dts.push(readings.iter().filter_map(|x| match x {
+                Reading::Tick { dt } => Some(dt.as_millis()),
+                _ => None,
+            }).collect::<Vec<_>>());
+        }
+        assert_eq!(metronome.tick_number(), 7);
+        batches.push(dts);
+    }
+    assert_eq!(batches, &[
+        vec![vec![100], vec![100, 100, 100, 100, 100], vec![100]],
+        vec![vec![100], vec![500], vec![100]],
+    ]);
+}
+#[test]
+fn peek() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    now_source.borrow_mut().now = Duration::from_millis(1000);
+    metronome.sample(Mode::OneFramePerTick).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_millis(1200);
+    {
+        let mut readings = metronome.sample(Mode::OneFramePerTick);
+        let tick = Reading::Tick { dt: Duration::from_millis(100) };
+        assert_eq!(readings.peek(), Some(&tick));
+        assert_eq!(readings.peek(), Some(&tick));
+        assert_eq!(readings.next(), Some(tick));
+        assert_eq!(readings.peek(), Some(&tick));
+        assert_eq!(readings.next(), Some(tick));
+        let frame = Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 };
+        assert_eq!(readings.peek(), Some(&frame));
+        assert!(readings.will_produce_frame());
+        assert_eq!(readings.next(), Some(frame));
+        assert!(!readings.will_produce_frame());
+        assert_eq!(readings.peek(), None);
+        assert_eq!(readings.next(), None);
+    }
+    assert_eq!(metronome.tick_number(), 3);
+}
+#[test]
+fn clock_correction() {
+    for (step, backward) in [(50, false), (50, true), (550, false)] {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+        #[cfg(not(feature="no_std"))]
+        metronome.set_history_depth(2);
+        now_source.borrow_mut().now = Duration::from_millis(1000);
+        metronome.sample(Mode::OneFramePerTick).for_each(drop);
+        now_source.borrow_mut().now = Duration::from_millis(1130);
+        metronome.sample(Mode::OneFramePerTick).for_each(drop);
+        // The clock is stepped, and we're told about it.
+        let (now, next_tick) = if backward { (1130 - step, 1200 - step) } else { (1130 + step, 1200 + step) };
+        now_source.borrow_mut().now = Duration::from_millis(now);
+        metronome.apply_clock_correction(Duration::from_millis(step), backward);
+        assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+            Reading::Idle { duration: Duration::from_millis(70) },
+        ]);
+        now_source.borrow_mut().now = Duration::from_millis(next_tick);
+        assert_eq!(metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+            tick(100_000_000),
+            Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+        ]);
+        assert_eq!(metronome.tick_number(), 3);
+        #[cfg(not(feature="no_std"))]
+        assert_eq!(metronome.tick_history(), &[
+            Duration::from_millis(next_tick - 100),
+            Duration::from_millis(next_tick),
+        ]);
+    }
+}
+#[test]
+fn backward_tolerance() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    metronome.set_backward_tolerance(Duration::from_millis(30));
+    // An absorbed jump isn't a stall, either
+    metronome.set_clock_stall_threshold(1);
+    let sample_at = |metronome: &mut Metronome<_>, millis| {
+        now_source.borrow_mut().now = Duration::from_millis(millis);
+        metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>()
+    };
+    let frame = |phase| Reading::Frame { phase, interpolating: true, on_tick_boundary: false, output_id: 0 };
+    sample_at(&mut metronome, 1000);
+    sample_at(&mut metronome, 1150);
+    // Back 30ms: as if the clock had stood still, so there's nothing new to
+    // render.
+    assert_eq!(sample_at(&mut metronome, 1120), &[]);
+    assert_eq!(metronome.last_now(), Some(Duration::from_millis(1150)));
+    assert_eq!(sample_at(&mut metronome, 1160), &[frame(0.6)]);
+    // Back 110ms: too far.
+    assert_eq!(sample_at(&mut metronome, 1050), &[
+        Reading::TimeWentBackwards,
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    assert_eq!(metronome.frame_bracket(), None);
+    // but the same instant twice still is
+    assert!(sample_at(&mut metronome, 1050).contains(&Reading::ClockStalled));
+}
+#[cfg(not(feature="no_std"))]
+#[test]
+fn windowed_stats() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    assert_eq!(metronome.windowed_stats(Duration::from_secs(1)), WindowedStats::default());
+    metronome.set_stats_window(Duration::from_secs(2));
+    // 20ms of work per frame, at 10 frames per second, for 3 seconds
+    while now_source.borrow().now < Duration::from_secs(3) {
+        let readings = metronome.sample(Mode::OneFramePerTick).collect::<Vec<_>>();
+        for reading in readings {
+            match reading {
+                Reading::Frame { .. } => now_source.borrow_mut().now += Duration::from_millis(20),
+                Reading::Idle { duration } => now_source.borrow_mut().now += duration,
+                _ => (),
+            }
+        }
+    }
+    for seconds in [1, 2, 5] {
+        let stats = metronome.windowed_stats(Duration::from_secs(seconds));
+        assert_eq!(stats.ticks_per_second, 10.0);
+        assert_eq!(stats.frames_per_second, 10.0);
+        assert!((stats.idle_fraction - 0.8).abs() < 1e-6, "{:?}", stats);
+    }
+    // Peeking at the clock between samples doesn't move the window.
+    let before = metronome.windowed_stats(Duration::from_secs(1));
+    now_source.borrow_mut().now += Duration::from_millis(700);
+    metronome.would_idle(Mode::OneFramePerTick);
+    metronome.grid_error();
+    metronome.time_until_next_frame(Mode::OneFramePerTick);
+    assert_eq!(metronome.windowed_stats(Duration::from_secs(1)), before);
+}
+#[test]
+fn slow_tick() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(30, 1), 10);
+    metronome.set_slow_tick_divisor(10);
+    let mut slow_ticks = vec![];
+    for n in 0 .. 25 {
+        now_source.borrow_mut().now = Duration::from_nanos(n * 1_000_000_000 / 30);
+        let readings = metronome.sample(Mode::TickOnly).collect::<Vec<_>>();
+        if readings.contains(&Reading::SlowTick) {
+            assert_eq!(readings.last(), Some(&Reading::SlowTick));
+            slow_ticks.push(metronome.tick_number());
+        }
+    }
+    assert_eq!(slow_ticks, &[10, 20]);
+    // In the middle of a batch, too
+    now_source.borrow_mut().now = Duration::from_nanos(31 * 1_000_000_000 / 30);
+    let readings = metronome.sample(Mode::TickOnly).collect::<Vec<_>>();
+    let slow = readings.iter().position(|x| *x == Reading::SlowTick);
+    assert_eq!((readings.len(), slow), (8, Some(5)));
+    assert_eq!(metronome.tick_number(), 32);
+}
+#[test]
+fn callback_now_source() {
+    let now_source = RefCell::new(CallbackNowSource::new());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(25, 1), 5);
+    let mut frames = vec![];
+    // 100Hz vsync
+    for vsync in 0 .. 9 {
+        now_source.borrow_mut().push(Duration::from_millis(vsync * 10));
+        let readings = metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>();
+        let mut batch = readings.iter().filter_map(|x| match x {
+            Reading::Frame { phase, .. } => Some(*phase),
+            _ => None,
+        });
+        frames.push(batch.next().unwrap());
+        assert_eq!(batch.next(), None);
+    }
+    assert_eq!(frames, &[1.0, 0.25, 0.5, 0.75, 1.0, 0.25, 0.5, 0.75, 1.0]);
+    // Sampling again without a push finds nothing new to render.
+    assert!(!metronome.sample(Mode::UnlimitedFrames).any(|x| matches!(x, Reading::Frame { .. })));
+}
+#[test]
+fn render_once() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+    now_source.borrow_mut().now = Duration::from_millis(1000);
+    metronome.sample(Mode::TickOnly).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_millis(1040);
+    assert_eq!(metronome.render_once(), 1.0);
+    assert_eq!(metronome.last_now(), Some(Duration::from_millis(1000)));
+    assert_eq!(metronome.sample(Mode::TickOnly).collect::<Vec<_>>(), &[
+        Reading::Idle { duration: Duration::from_millis(60) },
+    ]);
+    // Where a tick has been delivered early, it's in between.
+    metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+    now_source.borrow_mut().now = Duration::from_millis(1070);
+    assert_eq!(metronome.render_once(), 0.7);
+    assert_eq!(metronome.sample(Mode::UnlimitedFrames).collect::<Vec<_>>(), &[
+        Reading::Frame { phase: 0.7, interpolating: true, on_tick_boundary: false, output_id: 0 },
+    ]);
+}
+#[test]
+fn recheck_idle() {
+    for recheck in [false, true] {
+        let now_source = RefCell::new(FakeNowSource::default());
+        let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 5);
+        metronome.set_recheck_idle(recheck);
+        metronome.sample(Mode::OneFramePerTick).for_each(drop);
+        // Time passes between sampling and getting around to the readings.
+        for (sample_at, delay, idle) in [(30, 20, if recheck { Some(50) } else { Some(70) }),
+                                          (30, 80, if recheck { None } else { Some(70) })] {
+            now_source.borrow_mut().now = Duration::from_millis(sample_at);
+            let mut readings = metronome.sample(Mode::OneFramePerTick);
+            now_source.borrow_mut().now += Duration::from_millis(delay);
+            assert_eq!(readings.next(), idle.map(|x| Reading::Idle { duration: Duration::from_millis(x) }));
+            assert_eq!(readings.next(), None);
+        }
+    }
+}
+#[cfg(not(feature="no_std"))]
+#[test]
+fn preview() {
+    let rate = Rate::per_second(4, 1);
+    let millis = Duration::from_millis;
+    assert_eq!(super::preview(rate, Mode::OneFramePerTick, millis(0), millis(100)), &[
+        Reading::Idle { duration: millis(150) },
+    ]);
+    assert_eq!(super::preview(rate, Mode::OneFramePerTick, millis(0), millis(600)), &[
+        tick(250_000_000),
+        tick(250_000_000),
+        Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+    ]);
+    assert_eq!(super::preview(rate, Mode::TickOnly, millis(1000), millis(1250)), &[
+        tick(250_000_000),
+    ]);
+    assert_eq!(super::preview(rate, Mode::TargetFramesPerSecond(Rate::per_second(8, 1)), millis(0), millis(130)), &[
+        tick(250_000_000),
+        Reading::Frame { phase: 0.5, interpolating: true, on_tick_boundary: false, output_id: 0 },
+    ]);
+    assert_eq!(super::preview(rate, Mode::UnlimitedFrames, millis(500), millis(500)), &[]);
+}
+#[test]
+fn interpolating() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    let interpolating = |readings: Vec<Reading>| readings.into_iter().find_map(|x| match x {
+        Reading::Frame { interpolating, .. } => Some(interpolating),
+        _ => None,
+    });
+    // The very first frame has no previous tick to interpolate from.
+    assert_eq!(interpolating(metronome.sample(Mode::UnlimitedFrames).collect()), Some(false));
+    now_source.borrow_mut().now = Duration::from_millis(50);
+    assert_eq!(interpolating(metronome.sample(Mode::UnlimitedFrames).collect()), Some(true));
+    // OneFramePerTick never interpolates.
+    now_source.borrow_mut().now = Duration::from_millis(250);
+    assert_eq!(interpolating(metronome.sample(Mode::OneFramePerTick).collect()), Some(false));
+}
+#[test]
+fn ticks_ahead() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(100, 1), 10);
+    assert_eq!(metronome.ticks_ahead(), 0);
+    // A frame every microsecond, for a tenth of a second
+    for micros in 0 ..= 100_000 {
+        now_source.borrow_mut().now = Duration::from_micros(micros);
+        metronome.sample(Mode::UnlimitedFrames).for_each(drop);
+        // ticks are due at 0, 10ms, 20ms...
+        let due = micros / 10_000 + 1;
+        assert!(metronome.ticks_ahead() <= 1);
+        assert_eq!(metronome.tick_number() - metronome.ticks_ahead() as u64, due, "at {}us", micros);
+    }
+}
+#[test]
+fn hypothetical_phase() {
+    let now_source = RefCell::new(FakeNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    let sixty = Rate::per_second(60, 1);
+    let thirty = Rate::per_second(30, 1);
+    assert_eq!(metronome.hypothetical_phase(Duration::ZERO, sixty), None);
+    metronome.sample(Mode::TargetFramesPerSecond(sixty)).for_each(drop);
+    let millis = Duration::from_millis;
+    // 60fps frames land on 66.67ms and 83.33ms, 30fps frames only on 66.67ms
+    assert_eq!(metronome.hypothetical_phase(millis(80), sixty), Some(4.0 / 6.0));
+    assert_eq!(metronome.hypothetical_phase(millis(80), thirty), Some(4.0 / 6.0));
+    assert_eq!(metronome.hypothetical_phase(millis(90), sixty), Some(5.0 / 6.0));
+    assert_eq!(metronome.hypothetical_phase(millis(90), thirty), Some(4.0 / 6.0));
+    // both on the tick at 100ms
+    assert_eq!(metronome.hypothetical_phase(millis(100), sixty), Some(1.0));
+    assert_eq!(metronome.hypothetical_phase(millis(100), thirty), Some(1.0));
+    assert_eq!(metronome.hypothetical_phase(millis(340), thirty), Some(1.0 / 3.0));
+    // The live frame pacing doesn't notice any of this
+    now_source.borrow_mut().now = Duration::from_nanos(16_666_667);
+    assert_eq!(metronome.sample(Mode::TargetFramesPerSecond(sixty)).collect::<Vec<_>>(), &[
+        tick(100_000_000),
+        Reading::Frame { phase: 1.0 / 6.0, interpolating: true, on_tick_boundary: false, output_id: 0 },
+    ]);
+}
+#[test]
+fn sample_and_idle() {
+    #[derive(Default)]
+    struct SleepyNowSource { now: Duration, slept: Vec<Duration> }
+    impl NowSource for SleepyNowSource {
+        type Instant = Duration;
+        fn now(&mut self) -> Duration { self.now }
+        fn sleep(&mut self, how_long: Duration) {
+            self.slept.push(how_long);
+            self.now += how_long;
+        }
+    }
+    let now_source = RefCell::new(SleepyNowSource::default());
+    let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+    let frame = Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 };
+    assert_eq!(metronome.sample_and_idle(Mode::OneFramePerTick).collect::<Vec<_>>(), &[tick(100_000_000), frame]);
+    now_source.borrow_mut().now = Duration::from_millis(30);
+    assert_eq!(metronome.sample_and_idle(Mode::OneFramePerTick).collect::<Vec<_>>(), &[
+        Reading::Idle { duration: Duration::from_millis(70) },
     ]);
+    assert_eq!(now_source.borrow().slept, &[Duration::from_millis(70)]);
+    assert_eq!(metronome.sample_and_idle(Mode::OneFramePerTick).collect::<Vec<_>>(), &[tick(100_000_000), frame]);
+    // plain `sample` never sleeps
+    metronome.sample(Mode::OneFramePerTick).for_each(drop);
+    assert_eq!(now_source.borrow().slept.len(), 1);
 }
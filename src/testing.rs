@@ -0,0 +1,91 @@
+//! Table-driven tests of a `Metronome`'s readings, the same way `ftvf` tests
+//! itself. Only available with the `test-util` feature, and not with
+//! `no_std`.
+//!
+//! Write down a timeline, as a list of [`TestCmd`](enum.TestCmd.html)s, and
+//! the exact readings each sample must produce, and hand it to
+//! [`run_test`](fn.run_test.html). You supply the `Metronome` and a closure
+//! that moves your `NowSource` to a given instant, so this works with your
+//! own `NowSource` as well as with `FakeNowSource`.
+//!
+//! ```rust
+//! # use ftvf::*;
+//! # use ftvf::testing::{run_test, TestCmd::*};
+//! # use std::{cell::RefCell, time::Duration};
+//! let now_source = RefCell::new(FakeNowSource::default());
+//! let mut metronome = Metronome::new(&now_source, Rate::per_second(10, 1), 10);
+//! let tick = Reading::Tick { dt: Duration::from_millis(100) };
+//! run_test(&mut metronome, |now| now_source.borrow_mut().now = now, &[
+//!     Sample(Mode::OneFramePerTick, &[
+//!         tick,
+//!         Reading::Frame { phase: 1.0, interpolating: false, on_tick_boundary: true, output_id: 0 },
+//!     ]),
+//!     SetNow(Duration::from_millis(50)),
+//!     Sample(Mode::OneFramePerTick, &[
+//!         Reading::Idle { duration: Duration::from_millis(50) },
+//!     ]),
+//!     SetTickrate(Rate::per_second(20, 1)),
+//!     SetNow(Duration::from_millis(100)),
+//!     Sample(Mode::TickOnly, &[
+//!         Reading::TickrateChanged { new: Rate::per_second(20, 1) },
+//!         // the new tickrate applies from the tick at zero onward
+//!         Reading::Tick { dt: Duration::from_millis(50) },
+//!         Reading::Tick { dt: Duration::from_millis(50) },
+//!     ]),
+//! ]);
+//! ```
+
+use super::*;
+
+/// One step of a timeline passed to [`run_test`](fn.run_test.html).
+#[derive(Debug)]
+pub enum TestCmd<'a, I> {
+    /// Move the `NowSource` to this instant, using the closure passed to
+    /// `run_test`.
+    SetNow(I),
+    /// Sample in this mode, and check that exactly these readings come out,
+    /// in this order.
+    Sample(Mode, &'a [Reading]),
+    /// Call [`Metronome::set_tickrate`](../struct.Metronome.html#method.set_tickrate).
+    SetTickrate(Rate),
+}
+
+/// Runs every command in `cmds` against `metronome`, in order, calling
+/// `set_now` for each `SetNow`. Also checks that each batch's `size_hint`
+/// was honest.
+///
+/// PANICS at the first `Sample` that doesn't produce exactly the expected
+/// readings, after printing it, the commands leading up to it, and what it
+/// got instead.
+pub fn run_test<N: NowSource>(metronome: &mut Metronome<N>, mut set_now: impl FnMut(N::Instant), cmds: &[TestCmd<N::Instant>])
+where N::Instant: Clone + core::fmt::Debug {
+    let mut bad = None;
+    for (n, cmd) in cmds.iter().enumerate() {
+        match cmd {
+            TestCmd::SetNow(now) => set_now(now.clone()),
+            TestCmd::Sample(mode, readings) => {
+                let readings_iter = metronome.sample(*mode);
+                let (_, upper) = readings_iter.size_hint();
+                let check: Vec<Reading> = readings_iter.collect();
+                if upper.is_some_and(|upper| upper < check.len()) {
+                    bad = Some((n, format!("size_hint {:?} < {}", upper, check.len())));
+                    break;
+                }
+                if &check[..] != *readings {
+                    bad = Some((n, format!("got {:?}", check)));
+                    break;
+                }
+            },
+            TestCmd::SetTickrate(rate) => metronome.set_tickrate(*rate),
+        }
+    }
+    if let Some((index, explanation)) = bad {
+        eprintln!("Test failed!");
+        for (n, cmd) in cmds.iter().enumerate().take(index).skip(index.saturating_sub(10)) {
+            eprintln!("OK\t[{}] = {:?}", n, cmd);
+        }
+        eprintln!("BAD\t[{}] = {:?}", index, cmds[index]);
+        eprintln!("{}", explanation);
+        panic!("Test failed at command {}: {}", index, explanation);
+    }
+}
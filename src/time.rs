@@ -2,8 +2,18 @@
 mod realtime;
 #[cfg(not(feature="no_std"))]
 pub use realtime::RealtimeNowSource;
+#[cfg(all(feature="hires_clock", not(feature="no_std"), any(target_os="linux", target_os="android", target_vendor="apple", windows)))]
+mod hires;
+#[cfg(all(feature="hires_clock", not(feature="no_std"), any(target_os="linux", target_os="android", target_vendor="apple", windows)))]
+pub use hires::{HiresInstant, HiresNowSource};
+#[cfg(not(feature="no_std"))]
+mod scripted;
+#[cfg(not(feature="no_std"))]
+pub use scripted::ScriptedNowSource;
 mod fake;
 pub use fake::*;
+mod callback;
+pub use callback::CallbackNowSource;
 
 use core::{
     ops::Deref,
@@ -27,6 +37,10 @@ pub trait NowSource {
     type Instant: TemporalSample;
     /// Return a point in time representing Now.
     fn now(&mut self) -> Self::Instant;
+    /// Wait for `how_long` to pass. Only called by
+    /// [`Metronome::sample_and_idle`](struct.Metronome.html#method.sample_and_idle).
+    /// The default does nothing, since not every source has a way to wait.
+    fn sleep(&mut self, _how_long: Duration) {}
 }
 
 impl<T: Deref<Target=RefCell<N>>, N: NowSource> NowSource for T {
@@ -34,10 +48,20 @@ impl<T: Deref<Target=RefCell<N>>, N: NowSource> NowSource for T {
     fn now(&mut self) -> N::Instant {
         self.borrow_mut().now()
     }
+    fn sleep(&mut self, how_long: Duration) {
+        self.borrow_mut().sleep(how_long)
+    }
 }
 
 /// A type that represents a particular point in time. You only need to worry
 /// about it if you're implementing your own timing routines.
+///
+/// The underlying clock can be as small as you like, even a wrapping counter,
+/// as long as `ftvf` never has to compare two instants that are more than
+/// half a wrap apart. For a wrapping counter, the comparisons and
+/// `time_since` should treat whichever direction is shorter as the truth,
+/// with `time_since` returning `None` when the origin is (apparently) in the
+/// future.
 pub trait TemporalSample : Sized + Clone + PartialOrd + PartialEq {
     /// If this TemporalSample is *after* the given origin, return the
     /// `Duration` that has passed since that point. If this TemporalSample is
@@ -54,5 +78,272 @@ pub trait TemporalSample : Sized + Clone + PartialOrd + PartialEq {
     fn advance_by(&mut self, amount: Duration) {
         *self = self.advanced_by(amount);
     }
+    /// As `advanced_by`, but returns `None` instead of overflowing if the
+    /// result can't be represented. If your type has a limited range, you
+    /// should implement this, so that running out of time produces a
+    /// [`Reading::EndOfTime`](enum.Reading.html#variant.EndOfTime) instead of
+    /// wrapping or saturating. The default implementation just does
+    /// `Some(self.advanced_by(amount))`.
+    fn checked_advanced_by(&self, amount: Duration) -> Option<Self> {
+        Some(self.advanced_by(amount))
+    }
+    /// Return a new TemporalSample that is this much time into the *past*, or
+    /// `None` if that can't be represented. The default implementation always
+    /// returns `None`, which just means that features that need to look
+    /// backwards in time won't work with your type.
+    fn rewound_by(&self, _amount: Duration) -> Option<Self> {
+        None
+    }
+}
+
+/// Implements [`TemporalSample`](trait.TemporalSample.html) for a type that
+/// already has the right operators: `Clone`, `PartialOrd`,
+/// `Add<Duration, Output=Self>`, and `Sub<Self, Output=Duration>`. This is
+/// handy when wrapping a platform clock:
+///
+/// ```rust
+/// # use ftvf::*;
+/// # use core::{ops::{Add, Sub}, time::Duration};
+/// #[derive(Clone, Copy, PartialEq, PartialOrd)]
+/// struct Ticks(Duration);
+/// impl Add<Duration> for Ticks {
+///     type Output = Ticks;
+///     fn add(self, rhs: Duration) -> Ticks { Ticks(self.0 + rhs) }
+/// }
+/// impl Sub for Ticks {
+///     type Output = Duration;
+///     fn sub(self, rhs: Ticks) -> Duration { self.0 - rhs.0 }
+/// }
+/// temporal_sample_via_ops!(Ticks);
+/// ```
+///
+/// `time_since` compares the two instants first, so `Sub` is never asked
+/// to go negative. If your type also has `Sub<Duration, Output=Self>`, pass
+/// `rewind` as well, e.g. `temporal_sample_via_ops!(Ticks, rewind)`, to
+/// implement `rewound_by` too. (It, too, must not go out of range; the
+/// generated `rewound_by` can't check for that.)
+#[macro_export]
+macro_rules! temporal_sample_via_ops {
+    ($type:ty) => {
+        impl $crate::TemporalSample for $type {
+            fn time_since(&self, origin: &Self) -> Option<::core::time::Duration> {
+                if *self >= *origin { Some(self.clone() - origin.clone()) }
+                else { None }
+            }
+            fn advanced_by(&self, amount: ::core::time::Duration) -> Self {
+                self.clone() + amount
+            }
+        }
+    };
+    ($type:ty, rewind) => {
+        impl $crate::TemporalSample for $type {
+            fn time_since(&self, origin: &Self) -> Option<::core::time::Duration> {
+                if *self >= *origin { Some(self.clone() - origin.clone()) }
+                else { None }
+            }
+            fn advanced_by(&self, amount: ::core::time::Duration) -> Self {
+                self.clone() + amount
+            }
+            fn rewound_by(&self, amount: ::core::time::Duration) -> Option<Self> {
+                Some(self.clone() - amount)
+            }
+        }
+    };
+}
+
+/// Busy-wait until `source` says it is at least `target`. This is a portable
+/// way to wait out a [`Reading::Idle`](enum.Reading.html#variant.Idle) when
+/// there is no real way to sleep, such as on bare metal:
+///
+/// ```rust
+/// # use ftvf::*;
+/// # let mut now_source = FakeNowSource::default();
+/// # let duration = core::time::Duration::ZERO;
+/// let target = now_source.now().advanced_by(duration);
+/// spin_until(&mut now_source, target);
+/// ```
+///
+/// Spinning keeps the CPU fully busy the whole time, consuming as much power
+/// as it possibly can while doing nothing useful. If your platform has *any*
+/// way to sleep or wait for an interrupt, use that instead.
+pub fn spin_until<N: NowSource>(source: &mut N, target: N::Instant) {
+    while source.now() < target {
+        core::hint::spin_loop();
+    }
 }
 
+/// Check that `T`'s implementation of
+/// [`TemporalSample`](trait.TemporalSample.html) obeys the rules `ftvf`
+/// relies on, starting from `base` and going up to an hour either way. For
+/// a bunch of durations `d`, this checks that:
+///
+/// - `base.advanced_by(d).time_since(&base) == Some(d)`, exactly, down to the
+///   nanosecond
+/// - `base.time_since(&base.advanced_by(d))` is `None` (unless `d` is zero)
+/// - the later instant compares greater than `base`
+/// - `advance_by` and `checked_advanced_by` agree with `advanced_by`
+/// - advancing in two steps is the same as advancing in one
+/// - `rewound_by`, if it returns anything, undoes `advanced_by`
+///
+/// Returns a description of the first rule that was broken. Only available
+/// with the `test-util` feature, and not with `no_std`. Call it from your own
+/// tests:
+///
+/// ```rust
+/// # use ftvf::*;
+/// # use core::time::Duration;
+/// validate_temporal_sample(Duration::from_secs(1_000_000)).unwrap();
+/// ```
+#[cfg(all(any(test, feature="test-util"), not(feature="no_std")))]
+pub fn validate_temporal_sample<T: TemporalSample>(base: T) -> Result<(), std::string::String> {
+    use std::format;
+    const DURATIONS: [Duration; 11] = [
+        Duration::ZERO,
+        Duration::from_nanos(1),
+        Duration::from_nanos(2),
+        Duration::from_nanos(999),
+        Duration::from_micros(1),
+        Duration::from_nanos(1_000_001),
+        Duration::from_nanos(16_666_667),
+        Duration::from_secs(1),
+        Duration::new(1, 999_999_999),
+        Duration::from_secs(60),
+        Duration::from_secs(3600),
+    ];
+    if base.time_since(&base) != Some(Duration::ZERO) {
+        return Err(format!("base.time_since(&base) was {:?}, not Some(0ns)", base.time_since(&base)))
+    }
+    for d in DURATIONS {
+        let later = base.advanced_by(d);
+        if later.time_since(&base) != Some(d) {
+            return Err(format!("advancing by {:?} and then measuring gave {:?}", d, later.time_since(&base)))
+        }
+        if d.is_zero() {
+            if later != base {
+                return Err("advancing by zero gave an instant not equal to the original".into())
+            }
+        } else {
+            if base.time_since(&later).is_some() {
+                return Err(format!("time_since an instant {:?} in the future gave {:?}, not None", d, base.time_since(&later)))
+            }
+            if later <= base {
+                return Err(format!("an instant {:?} later didn't compare greater", d))
+            }
+        }
+        let mut advanced = base.clone();
+        advanced.advance_by(d);
+        if advanced != later {
+            return Err(format!("advance_by({:?}) disagreed with advanced_by", d))
+        }
+        if let Some(checked) = base.checked_advanced_by(d) {
+            if checked != later {
+                return Err(format!("checked_advanced_by({:?}) disagreed with advanced_by", d))
+            }
+        }
+        for e in DURATIONS {
+            if later.advanced_by(e) != base.advanced_by(d + e) {
+                return Err(format!("advancing by {:?} and then {:?} disagreed with advancing by {:?}", d, e, d + e))
+            }
+        }
+        if let Some(earlier) = base.rewound_by(d) {
+            if base.time_since(&earlier) != Some(d) {
+                return Err(format!("rewinding by {:?} and then measuring gave {:?}", d, base.time_since(&earlier)))
+            }
+            if earlier.advanced_by(d) != base {
+                return Err(format!("rewinding by {:?} and then advancing by the same wasn't a round trip", d))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    /// Advances by one millisecond every time it's asked what time it is.
+    struct TickingNowSource { now: Duration, reads: u32 }
+    impl NowSource for TickingNowSource {
+        type Instant = Duration;
+        fn now(&mut self) -> Duration {
+            self.reads += 1;
+            self.now += Duration::from_millis(1);
+            self.now
+        }
+    }
+    #[test]
+    fn spin() {
+        let mut source = TickingNowSource { now: Duration::ZERO, reads: 0 };
+        spin_until(&mut source, Duration::from_millis(10));
+        assert_eq!(source.now, Duration::from_millis(10));
+        assert_eq!(source.reads, 10);
+        // Already there? Only look once.
+        spin_until(&mut source, Duration::from_millis(5));
+        assert_eq!(source.reads, 11);
+    }
+    #[test]
+    #[cfg(not(feature="no_std"))]
+    fn via_ops() {
+        use std::{ops::{Add, Sub}, time::Instant};
+        use crate::{Metronome, Mode, Rate, Reading};
+        #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+        struct Wrapped(Instant);
+        impl Add<Duration> for Wrapped {
+            type Output = Wrapped;
+            fn add(self, rhs: Duration) -> Wrapped { Wrapped(self.0 + rhs) }
+        }
+        impl Sub for Wrapped {
+            type Output = Duration;
+            fn sub(self, rhs: Wrapped) -> Duration { self.0 - rhs.0 }
+        }
+        impl Sub<Duration> for Wrapped {
+            type Output = Wrapped;
+            fn sub(self, rhs: Duration) -> Wrapped { Wrapped(self.0 - rhs) }
+        }
+        temporal_sample_via_ops!(Wrapped, rewind);
+        let base = Wrapped(Instant::now());
+        let later = base.advanced_by(Duration::from_millis(5));
+        assert_eq!(later.time_since(&base), Some(Duration::from_millis(5)));
+        assert_eq!(base.time_since(&later), None);
+        assert_eq!(later.rewound_by(Duration::from_millis(5)), Some(base));
+        // And it's good enough for a `Metronome`.
+        struct WrappedNowSource(Wrapped);
+        impl NowSource for WrappedNowSource {
+            type Instant = Wrapped;
+            fn now(&mut self) -> Wrapped { self.0 }
+        }
+        let mut metronome = Metronome::new(WrappedNowSource(later), Rate::per_second(10, 1), 5);
+        assert!(metronome.sample(Mode::TickOnly).any(|x| matches!(x, Reading::Tick { .. })));
+        assert_eq!(metronome.instant_of_tick_offset(-1), Some(later - Duration::from_millis(100)));
+    }
+    #[test]
+    #[cfg(not(feature="no_std"))]
+    fn validate() {
+        assert_eq!(validate_temporal_sample(Duration::from_secs(3600)), Ok(()));
+        assert_eq!(validate_temporal_sample(Duration::ZERO), Ok(()));
+        assert_eq!(validate_temporal_sample(std::time::Instant::now()), Ok(()));
+        // Only microsecond precision, and no residual.
+        #[derive(Clone, Copy, PartialEq, PartialOrd)]
+        struct Coarse(u64);
+        impl TemporalSample for Coarse {
+            fn time_since(&self, origin: &Coarse) -> Option<Duration> {
+                self.0.checked_sub(origin.0).map(Duration::from_micros)
+            }
+            fn advanced_by(&self, amount: Duration) -> Coarse {
+                Coarse(self.0 + amount.as_micros() as u64)
+            }
+        }
+        assert_eq!(validate_temporal_sample(Coarse(0)), Err("advancing by 1ns and then measuring gave Some(0ns)".into()));
+        // Saturates instead of returning None.
+        #[derive(Clone, Copy, PartialEq, PartialOrd)]
+        struct Saturating(Duration);
+        impl TemporalSample for Saturating {
+            fn time_since(&self, origin: &Saturating) -> Option<Duration> {
+                Some(self.0.saturating_sub(origin.0))
+            }
+            fn advanced_by(&self, amount: Duration) -> Saturating {
+                Saturating(self.0 + amount)
+            }
+        }
+        assert_eq!(validate_temporal_sample(Saturating(Duration::ZERO)), Err("time_since an instant 1ns in the future gave Some(0ns), not None".into()));
+    }
+}
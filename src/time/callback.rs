@@ -0,0 +1,36 @@
+use core::time::Duration;
+
+use super::NowSource;
+
+/// A `NowSource` whose time only moves when you tell it to. Call
+/// [`push`](#method.push) from whatever callback gives you timestamps (say,
+/// a vsync or presentation callback), and `now()` returns the most recently
+/// pushed one. Time starts at zero.
+///
+/// Since the `Metronome` owns its `NowSource`, give it a
+/// `&RefCell<CallbackNowSource>` (or an `Rc` of one), and push through the
+/// `RefCell`. If you then sample once per push in
+/// [`Mode::UnlimitedFrames`](enum.Mode.html#variant.UnlimitedFrames), you
+/// get exactly one frame per callback, locked to its timing.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct CallbackNowSource {
+    latest: Duration,
+}
+
+impl CallbackNowSource {
+    /// Creates a `CallbackNowSource` whose time is zero.
+    pub fn new() -> CallbackNowSource {
+        CallbackNowSource::default()
+    }
+    /// Sets the time `now()` will return from now on.
+    pub fn push(&mut self, now: Duration) {
+        self.latest = now;
+    }
+}
+
+impl NowSource for CallbackNowSource {
+    type Instant = Duration;
+    fn now(&mut self) -> Duration {
+        self.latest
+    }
+}
@@ -36,4 +36,10 @@ impl TemporalSample for Duration {
     fn advanced_by(&self, amount: Duration) -> Duration {
         *self + amount
     }
+    fn checked_advanced_by(&self, amount: Duration) -> Option<Duration> {
+        self.checked_add(amount)
+    }
+    fn rewound_by(&self, amount: Duration) -> Option<Duration> {
+        self.checked_sub(amount)
+    }
 }
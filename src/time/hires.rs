@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use crate::{TemporalSample, NowSource};
+
+/// A [`NowSource`](trait.NowSource.html) that reads the platform's
+/// high-resolution monotonic counter directly, producing
+/// [`HiresInstant`](struct.HiresInstant.html)s. Otherwise just like
+/// [`RealtimeNowSource`](struct.RealtimeNowSource.html).
+///
+/// Only available with the `hires_clock` feature, and only on Windows, Linux,
+/// Android, and Apple platforms. On any other platform, this and
+/// `HiresInstant` don't exist.
+#[derive(Debug,Copy,Clone,Default)]
+pub struct HiresNowSource {}
+
+impl HiresNowSource {
+    pub fn new() -> HiresNowSource { HiresNowSource { } }
+}
+
+impl NowSource for HiresNowSource {
+    type Instant = HiresInstant;
+    fn now(&mut self) -> HiresInstant { HiresInstant::now() }
+    fn sleep(&mut self, how_long: Duration) { std::thread::sleep(how_long) }
+}
+
+/// An instant read straight from the platform's high-resolution monotonic
+/// counter, as produced by [`HiresNowSource`](struct.HiresNowSource.html).
+///
+/// On Windows, the counter is `QueryPerformanceCounter`. On Linux, Android,
+/// and Apple platforms, it's `clock_gettime(CLOCK_MONOTONIC_RAW)`, which
+/// (unlike `CLOCK_MONOTONIC`) is never slewed by NTP. The instant is stored
+/// as a `Duration` since the counter's own arbitrary origin, so, like a
+/// `Duration`, it gets no residual.
+#[derive(Debug,Copy,Clone,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct HiresInstant(Duration);
+
+impl HiresInstant {
+    /// Read the counter.
+    pub fn now() -> HiresInstant { HiresInstant(platform::now()) }
+}
+
+impl TemporalSample for HiresInstant {
+    fn time_since(&self, origin: &Self) -> Option<Duration> {
+        self.0.checked_sub(origin.0)
+    }
+    fn advanced_by(&self, amount: Duration) -> Self {
+        HiresInstant(self.0 + amount)
+    }
+    fn checked_advanced_by(&self, amount: Duration) -> Option<Self> {
+        self.0.checked_add(amount).map(HiresInstant)
+    }
+    fn rewound_by(&self, amount: Duration) -> Option<Self> {
+        self.0.checked_sub(amount).map(HiresInstant)
+    }
+}
+
+#[cfg(any(target_os="linux", target_os="android", target_vendor="apple"))]
+mod platform {
+    use std::{os::raw::{c_int, c_long}, time::Duration};
+    #[repr(C)]
+    struct Timespec {
+        tv_sec: c_long,
+        tv_nsec: c_long,
+    }
+    // The same number on Linux and on Apple platforms.
+    const CLOCK_MONOTONIC_RAW: c_int = 4;
+    extern "C" {
+        fn clock_gettime(clock: c_int, tp: *mut Timespec) -> c_int;
+    }
+    pub fn now() -> Duration {
+        let mut tp = Timespec { tv_sec: 0, tv_nsec: 0 };
+        let result = unsafe { clock_gettime(CLOCK_MONOTONIC_RAW, &mut tp) };
+        assert_eq!(result, 0, "clock_gettime(CLOCK_MONOTONIC_RAW) failed");
+        Duration::new(tp.tv_sec as u64, tp.tv_nsec as u32)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::time::Duration;
+    #[link(name="kernel32")]
+    extern "system" {
+        fn QueryPerformanceCounter(count: *mut i64) -> i32;
+        fn QueryPerformanceFrequency(frequency: *mut i64) -> i32;
+    }
+    pub fn now() -> Duration {
+        let (mut count, mut frequency) = (0, 0);
+        // Neither of these can fail on Windows XP or later.
+        unsafe {
+            QueryPerformanceCounter(&mut count);
+            QueryPerformanceFrequency(&mut frequency);
+        }
+        let (count, frequency) = (count as u128, frequency as u128);
+        // Whole seconds first, so that nothing overflows.
+        let nanos = count / frequency * 1_000_000_000
+            + count % frequency * 1_000_000_000 / frequency;
+        Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test] fn monotonic() {
+        let mut last = HiresInstant::now();
+        for _ in 0 .. 100_000 {
+            let now = HiresInstant::now();
+            assert!(now.time_since(&last).is_some(), "{:?} came after {:?}", now, last);
+            last = now;
+        }
+    }
+    #[test] fn hires_now_source() {
+        use crate::{Metronome, Mode, Rate, Reading};
+        let mut metronome = Metronome::new(HiresNowSource::new(), Rate::per_second(10, 1), 5);
+        assert!(metronome.sample(Mode::TickOnly).any(|x| matches!(x, Reading::Tick { .. })));
+    }
+    #[test] fn hires_residual() {
+        let base = HiresInstant::now();
+        for n in 1 .. 100 {
+            let duration = Duration::new(0, n);
+            assert_eq!(base.advanced_by(duration).time_since(&base), Some(duration));
+        }
+    }
+}
@@ -5,7 +5,7 @@ use crate::{TemporalSample, NowSource};
 /// A [`NowSource`](trait.NowSource.html) that uses the standard Rust timing
 /// facilities to obtain its timing information. This is the default
 /// `NowSource`, and also the one you almost certainly want to use.
-#[derive(Debug,Copy,Clone)]
+#[derive(Debug,Copy,Clone,Default)]
 pub struct RealtimeNowSource {}
 
 impl RealtimeNowSource {
@@ -15,6 +15,7 @@ impl RealtimeNowSource {
 impl NowSource for RealtimeNowSource {
     type Instant = std::time::Instant;
     fn now(&mut self) -> Self::Instant { Self::Instant::now() }
+    fn sleep(&mut self, how_long: Duration) { std::thread::sleep(how_long) }
 }
 
 impl TemporalSample for Instant {
@@ -27,6 +28,12 @@ impl TemporalSample for Instant {
     fn advance_by(&mut self, amount: Duration) {
         *self += amount;
     }
+    fn checked_advanced_by(&self, amount: Duration) -> Option<Self> {
+        self.checked_add(amount)
+    }
+    fn rewound_by(&self, amount: Duration) -> Option<Self> {
+        self.checked_sub(amount)
+    }
 }
 
 #[cfg(test)]
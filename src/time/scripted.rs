@@ -0,0 +1,37 @@
+use std::{
+    time::Duration,
+    vec::Vec,
+};
+
+use super::NowSource;
+
+/// A fake `NowSource` that plays back a predetermined list of times. Each
+/// call to `now()` returns the next entry in `times`. Once the list runs out,
+/// the last entry is repeated forever. (An empty list is always zero.)
+///
+/// Handy for table-driven tests, where you know in advance exactly when each
+/// `sample` will happen.
+#[derive(Debug, Default, Clone)]
+pub struct ScriptedNowSource {
+    /// The times to return, in order.
+    pub times: Vec<Duration>,
+    /// The index of the entry the next call to `now()` will return.
+    pub index: usize,
+}
+
+impl ScriptedNowSource {
+    /// Creates a `ScriptedNowSource` that starts at the beginning of `times`.
+    pub fn new(times: Vec<Duration>) -> ScriptedNowSource {
+        ScriptedNowSource { times, index: 0 }
+    }
+}
+
+impl NowSource for ScriptedNowSource {
+    type Instant = Duration;
+    fn now(&mut self) -> Duration {
+        let ret = self.times.get(self.index).or(self.times.last())
+            .copied().unwrap_or_default();
+        if self.index < self.times.len() { self.index += 1 }
+        ret
+    }
+}